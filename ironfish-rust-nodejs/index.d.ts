@@ -25,7 +25,39 @@ export interface NativeSpendDescription {
   rootHash: Buffer
   nullifier: Buffer
 }
-export function verifyTransactions(serializedTransactions: Array<Buffer>): boolean
+/**
+ * Outcome of verifying one or more transactions. When invalid, says which
+ * check failed and where, so the caller doesn't have to verify again to
+ * find out.
+ */
+export interface NativeVerificationResult {
+  valid: boolean
+  /** Why the transaction is invalid */
+  error?: string
+  /**
+   * Name of the check that failed, such as "SpendProof" or
+   * "DuplicateNullifier"
+   */
+  check?: string
+  /** Index of the spend, output, or mint the check failed on */
+  index?: number
+  /** Index of the invalid transaction, when verifying several */
+  transactionIndex?: number
+}
+/**
+ * Verify a batch of transactions, such as the transactions of a block. If
+ * any is invalid, the result is for the first invalid transaction and says
+ * which one it is.
+ */
+export function verifyTransactions(serializedTransactions: Array<Buffer>): NativeVerificationResult
+/**
+ * Verify a batch of transactions, returning a result for each one rather
+ * than a single result for the whole batch. Transactions that can't be
+ * deserialized are reported as invalid.
+ */
+export function verifyTransactionsWithResults(serializedTransactions: Array<Buffer>): Array<NativeVerificationResult>
+export function isExpiredSequence(expirationSequence: number, currentSequence: number): boolean
+export function defaultExpirationSequence(currentSequence: number): number
 export interface Key {
   spending_key: string
   incoming_view_key: string
@@ -33,8 +65,26 @@ export interface Key {
   public_address: string
 }
 export function generateKey(): Key
-export function generateNewPublicAddress(privateKey: string): Key
+export interface DiversifiedAddress {
+  public_address: string
+  /**
+   * Index the address was derived at. Pass one more than this to get the
+   * next address.
+   */
+  diversifier_index: bigint
+}
+/**
+ * Generate the first valid address of the key at or after `from_index`. The
+ * default address of the key is returned when `from_index` is left out.
+ */
+export function generateNewPublicAddress(privateKey: string, fromIndex?: bigint | undefined | null): DiversifiedAddress
+export function exportEncryptedKey(privateKey: string, password: string): string
+export function importEncryptedKey(keystore: string, password: string): Key
+export function encryptedKeyFingerprint(keystore: string): string
+export function addressFingerprint(publicAddress: string): string
 export function initializeSapling(): void
+export function initializeSaplingFromPaths(spendPath: string, outputPath: string, mintPath: string, verifyOnly?: boolean | undefined | null, checked?: boolean | undefined | null): void
+export function configureProvingThreads(numThreads: number): void
 export function isValidPublicAddress(hexAddress: string): boolean
 export class BoxKeyPair {
   constructor()
@@ -91,15 +141,26 @@ export type NativeTransactionPosted = TransactionPosted
 export class TransactionPosted {
   constructor(jsBytes: Buffer)
   serialize(): Buffer
-  verify(): boolean
+  /**
+   * Verify the transaction. If it is invalid, the result names the check
+   * that failed and the spend, output, or mint it failed on.
+   */
+  verify(): NativeVerificationResult
   notesLength(): number
   getNote(index: number): Buffer
   spendsLength(): number
   getSpend(index: number): NativeSpendDescription
   fee(): bigint
   transactionSignature(): Buffer
-  hash(): Buffer
+  /**
+   * Hash of the transaction that the spend, mint, and binding signatures
+   * sign. Use `id` or `unsignedId` to identify a transaction.
+   */
+  signatureHash(): Buffer
+  id(): Buffer
+  unsignedId(): Buffer
   expirationSequence(): number
+  isExpired(currentSequence: number): boolean
 }
 export type NativeTransaction = Transaction
 export class Transaction {
@@ -129,7 +190,24 @@ export class Transaction {
    * aka: self.value_balance - intended_transaction_fee - change = 0
    */
   post(changeGoesTo: string | undefined | null, intendedTransactionFee: bigint): Buffer
+  /**
+   * Size in bytes of the posted transaction, computed without creating
+   * any proofs. Assumes a change output whenever the balance is positive.
+   */
+  estimatedSize(): number
+  /**
+   * Suggested fee for the posted transaction at the given fee rate, in ore
+   * per 1000 bytes.
+   */
+  estimateFee(feeRatePerKb: bigint): bigint
   setExpirationSequence(expirationSequence: number): void
+  /**
+   * Expire the transaction a default number of blocks after
+   * `current_sequence`, the sequence of the head of the chain. Without this
+   * or `set_expiration_sequence`, the transaction never expires.
+   */
+  setDefaultExpirationSequence(currentSequence: number): void
+  isExpired(currentSequence: number): boolean
 }
 export class FoundBlockResult {
   randomness: string
@@ -4,7 +4,9 @@
 
 use std::fmt::Display;
 
+use ironfish_rust::keys::EncryptedSpendingKey;
 use ironfish_rust::PublicAddress;
+use ironfish_rust::Sapling;
 use ironfish_rust::SaplingKey;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
@@ -44,9 +46,52 @@ pub fn generate_key() -> Key {
     }
 }
 
+#[napi(object)]
+pub struct DiversifiedAddress {
+    #[napi(js_name = "public_address")]
+    pub public_address: String,
+    /// Index the address was derived at. Pass one more than this to get the
+    /// next address.
+    #[napi(js_name = "diversifier_index")]
+    pub diversifier_index: BigInt,
+}
+
+/// Generate the first valid address of the key at or after `from_index`. The
+/// default address of the key is returned when `from_index` is left out.
 #[napi]
-pub fn generate_new_public_address(private_key: String) -> Result<Key> {
+pub fn generate_new_public_address(
+    private_key: String,
+    from_index: Option<BigInt>,
+) -> Result<DiversifiedAddress> {
     let sapling_key = SaplingKey::from_hex(&private_key).map_err(to_napi_err)?;
+    let from_index = from_index.map_or(0, |index| index.get_u64().1);
+    let (diversifier_index, address) = sapling_key
+        .next_valid_address(from_index)
+        .map_err(to_napi_err)?;
+
+    Ok(DiversifiedAddress {
+        public_address: address.hex_public_address(),
+        diversifier_index: BigInt::from(diversifier_index),
+    })
+}
+
+/// Encrypt a hex spending key with a password, returning the keystore as
+/// hex.
+#[napi]
+pub fn export_encrypted_key(private_key: String, password: String) -> Result<String> {
+    let sapling_key = SaplingKey::from_hex(&private_key).map_err(to_napi_err)?;
+    let keystore = sapling_key
+        .export_encrypted(&password)
+        .map_err(to_napi_err)?;
+
+    Ok(keystore.hex_keystore())
+}
+
+/// Decrypt a keystore created by `exportEncryptedKey`.
+#[napi]
+pub fn import_encrypted_key(keystore: String, password: String) -> Result<Key> {
+    let keystore = EncryptedSpendingKey::from_hex(&keystore).map_err(to_napi_err)?;
+    let sapling_key = SaplingKey::import_encrypted(&keystore, &password).map_err(to_napi_err)?;
 
     Ok(Key {
         spending_key: sapling_key.hex_spending_key(),
@@ -56,11 +101,67 @@ pub fn generate_new_public_address(private_key: String) -> Result<Key> {
     })
 }
 
+/// Fingerprint of the account a keystore belongs to, which can be read
+/// without the password.
+#[napi]
+pub fn encrypted_key_fingerprint(keystore: String) -> Result<String> {
+    let keystore = EncryptedSpendingKey::from_hex(&keystore).map_err(to_napi_err)?;
+
+    Ok(hex::encode(keystore.address_fingerprint()))
+}
+
+/// Fingerprint of a hex public address, to compare against
+/// `encryptedKeyFingerprint` when looking up the account of a keystore.
+#[napi]
+pub fn address_fingerprint(public_address: String) -> Result<String> {
+    let address = PublicAddress::from_hex(&public_address).map_err(to_napi_err)?;
+
+    Ok(hex::encode(EncryptedSpendingKey::fingerprint_address(
+        &address,
+    )))
+}
+
 #[napi]
 pub fn initialize_sapling() {
     let _ = sapling_bls12::SAPLING.clone();
 }
 
+/// Load the Sapling parameters from the given files instead of the ones
+/// embedded in the binary. With `verify_only`, only the verifying keys are
+/// read, and the resulting instance can't create proofs. With `checked`, every
+/// point in the proving parameters is validated as well, which is slow but
+/// should be used for parameters from an untrusted source.
+///
+/// Must be called before anything else uses the Sapling parameters.
+#[napi]
+pub fn initialize_sapling_from_paths(
+    spend_path: String,
+    output_path: String,
+    mint_path: String,
+    verify_only: Option<bool>,
+    checked: Option<bool>,
+) -> Result<()> {
+    let sapling = if verify_only.unwrap_or(false) {
+        Sapling::verifier_from_paths(spend_path, output_path, mint_path)
+    } else {
+        Sapling::from_paths(spend_path, output_path, mint_path, checked.unwrap_or(false))
+    }
+    .map_err(to_napi_err)?;
+
+    sapling_bls12::configure_sapling(sapling).map_err(to_napi_err)?;
+
+    let _ = sapling_bls12::SAPLING.clone();
+
+    Ok(())
+}
+
+/// Set the number of threads used to create the proofs of a transaction. By
+/// default, one thread per CPU is used.
+#[napi]
+pub fn configure_proving_threads(num_threads: u32) -> Result<()> {
+    ironfish_rust::transaction::configure_proving_threads(num_threads as usize).map_err(to_napi_err)
+}
+
 #[napi(constructor)]
 pub struct FoundBlockResult {
     pub randomness: String,
@@ -5,7 +5,8 @@
 use std::cell::RefCell;
 use std::convert::TryInto;
 
-use ironfish_rust::transaction::batch_verify_transactions;
+use ironfish_rust::errors::IronfishError;
+use ironfish_rust::transaction::{self, batch_verify_transactions_with_results, TransactionLimits};
 use ironfish_rust::{MerkleNoteHash, ProposedTransaction, PublicAddress, SaplingKey, Transaction};
 use napi::{bindgen_prelude::*, JsBuffer};
 use napi_derive::napi;
@@ -17,6 +18,63 @@ use super::spend_proof::NativeSpendDescription;
 use super::witness::JsWitness;
 use super::ENCRYPTED_NOTE_LENGTH;
 
+/// Outcome of verifying one or more transactions. When invalid, says which
+/// check failed and where, so the caller doesn't have to verify again to
+/// find out.
+#[napi(object)]
+pub struct NativeVerificationResult {
+    pub valid: bool,
+
+    /// Why the transaction is invalid
+    pub error: Option<String>,
+
+    /// Name of the check that failed, such as "SpendProof" or
+    /// "DuplicateNullifier"
+    pub check: Option<String>,
+
+    /// Index of the spend, output, or mint the check failed on
+    pub index: Option<u32>,
+
+    /// Index of the invalid transaction, when verifying several
+    pub transaction_index: Option<u32>,
+}
+
+impl NativeVerificationResult {
+    fn new(result: std::result::Result<(), IronfishError>, transaction_index: Option<u32>) -> Self {
+        let error = match result {
+            Ok(()) => {
+                return NativeVerificationResult {
+                    valid: true,
+                    error: None,
+                    check: None,
+                    index: None,
+                    transaction_index,
+                }
+            }
+            Err(error) => error,
+        };
+
+        let (check, index) = match &error {
+            IronfishError::InvalidTransaction(check) => {
+                (Some(check.name().to_string()), check.index())
+            }
+            IronfishError::DuplicateNullifier(duplicate) => (
+                Some("DuplicateNullifier".to_string()),
+                Some(duplicate.second.1),
+            ),
+            _ => (None, None),
+        };
+
+        NativeVerificationResult {
+            valid: false,
+            error: Some(error.to_string()),
+            check,
+            index: index.map(|index| index as u32),
+            transaction_index,
+        }
+    }
+}
+
 #[napi(js_name = "TransactionPosted")]
 pub struct NativeTransactionPosted {
     transaction: Transaction,
@@ -28,7 +86,9 @@ impl NativeTransactionPosted {
     pub fn new(js_bytes: JsBuffer) -> Result<NativeTransactionPosted> {
         let bytes = js_bytes.into_value()?;
 
-        let transaction = Transaction::read(bytes.as_ref()).map_err(to_napi_err)?;
+        let transaction =
+            Transaction::read_with_limits(bytes.as_ref(), &TransactionLimits::default())
+                .map_err(to_napi_err)?;
 
         Ok(NativeTransactionPosted { transaction })
     }
@@ -41,12 +101,11 @@ impl NativeTransactionPosted {
         Ok(Buffer::from(vec))
     }
 
+    /// Verify the transaction. If it is invalid, the result names the check
+    /// that failed and the spend, output, or mint it failed on.
     #[napi]
-    pub fn verify(&self) -> bool {
-        match self.transaction.verify() {
-            Ok(_) => true,
-            Err(_e) => false,
-        }
+    pub fn verify(&self) -> NativeVerificationResult {
+        NativeVerificationResult::new(self.transaction.verify(), None)
     }
 
     #[napi]
@@ -125,22 +184,49 @@ impl NativeTransactionPosted {
         Ok(Buffer::from(serialized_signature))
     }
 
+    /// Hash of the transaction that the spend, mint, and binding signatures
+    /// sign. Use `id` or `unsignedId` to identify a transaction.
     #[napi]
-    pub fn hash(&self) -> Buffer {
+    pub fn signature_hash(&self) -> Buffer {
         let hash = self.transaction.transaction_signature_hash();
 
         Buffer::from(hash.as_ref())
     }
 
+    /// Identifier committing to the full serialized transaction, including
+    /// its signatures.
+    #[napi]
+    pub fn id(&self) -> Buffer {
+        let id = self.transaction.id();
+
+        Buffer::from(id.as_ref())
+    }
+
+    /// Identifier of the transaction without its signatures.
+    #[napi]
+    pub fn unsigned_id(&self) -> Buffer {
+        let id = self.transaction.unsigned_id();
+
+        Buffer::from(id.as_ref())
+    }
+
     #[napi]
     pub fn expiration_sequence(&self) -> u32 {
         self.transaction.expiration_sequence()
     }
+
+    /// Whether the transaction has expired and can no longer be added to the
+    /// block at `current_sequence`.
+    #[napi]
+    pub fn is_expired(&self, current_sequence: u32) -> bool {
+        self.transaction.is_expired(current_sequence)
+    }
 }
 
 #[napi(js_name = "Transaction")]
 pub struct NativeTransaction {
     transaction: ProposedTransaction,
+    spender_key: SaplingKey,
 }
 
 #[napi]
@@ -149,25 +235,30 @@ impl NativeTransaction {
     pub fn new(spender_hex_key: String) -> Result<NativeTransaction> {
         let spender_key = SaplingKey::from_hex(&spender_hex_key).map_err(to_napi_err)?;
         Ok(NativeTransaction {
-            transaction: ProposedTransaction::new(spender_key),
+            transaction: ProposedTransaction::new(),
+            spender_key,
         })
     }
 
     /// Create a proof of a new note owned by the recipient in this transaction.
     #[napi]
-    pub fn receive(&mut self, note: &NativeNote) {
-        self.transaction.add_output(note.note.clone());
+    pub fn receive(&mut self, note: &NativeNote) -> Result<()> {
+        self.transaction
+            .add_output(note.note.clone())
+            .map_err(to_napi_err)
     }
 
     /// Spend the note owned by spender_hex_key at the given witness location.
     #[napi]
-    pub fn spend(&mut self, env: Env, note: &NativeNote, witness: Object) {
+    pub fn spend(&mut self, env: Env, note: &NativeNote, witness: Object) -> Result<()> {
         let w = JsWitness {
             cx: RefCell::new(env),
             obj: witness,
         };
 
-        self.transaction.add_spend(note.note.clone(), &w);
+        self.transaction
+            .add_spend(note.note.clone(), &w)
+            .map_err(to_napi_err)
     }
 
     /// Special case for posting a miners fee transaction. Miner fee transactions
@@ -177,7 +268,10 @@ impl NativeTransaction {
     /// as the miners fee.
     #[napi(js_name = "post_miners_fee")]
     pub fn post_miners_fee(&mut self) -> Result<Buffer> {
-        let transaction = self.transaction.post_miners_fee().map_err(to_napi_err)?;
+        let transaction = self
+            .transaction
+            .post_miners_fee(&self.spender_key)
+            .map_err(to_napi_err)?;
 
         let mut vec: Vec<u8> = vec![];
         transaction.write(&mut vec).map_err(to_napi_err)?;
@@ -209,7 +303,7 @@ impl NativeTransaction {
 
         let posted_transaction = self
             .transaction
-            .post(change_key, intended_transaction_fee_u64)
+            .post(&self.spender_key, change_key, intended_transaction_fee_u64)
             .map_err(to_napi_err)?;
 
         let mut vec: Vec<u8> = vec![];
@@ -218,23 +312,96 @@ impl NativeTransaction {
         Ok(Buffer::from(vec))
     }
 
+    /// Size in bytes of the posted transaction, computed without creating
+    /// any proofs. Assumes a change output whenever the balance is positive.
+    #[napi]
+    pub fn estimated_size(&self) -> u32 {
+        self.transaction.estimated_size() as u32
+    }
+
+    /// Suggested fee for the posted transaction at the given fee rate, in ore
+    /// per 1000 bytes.
+    #[napi]
+    pub fn estimate_fee(&self, fee_rate_per_kb: BigInt) -> i64n {
+        i64n(self.transaction.estimate_fee(fee_rate_per_kb.get_u64().1) as i64)
+    }
+
     #[napi]
     pub fn set_expiration_sequence(&mut self, expiration_sequence: u32) -> Undefined {
         self.transaction
             .set_expiration_sequence(expiration_sequence);
     }
+
+    /// Expire the transaction a default number of blocks after
+    /// `current_sequence`, the sequence of the head of the chain. Without this
+    /// or `set_expiration_sequence`, the transaction never expires.
+    #[napi]
+    pub fn set_default_expiration_sequence(&mut self, current_sequence: u32) -> Undefined {
+        self.transaction
+            .set_default_expiration_sequence(current_sequence);
+    }
+
+    #[napi]
+    pub fn is_expired(&self, current_sequence: u32) -> bool {
+        self.transaction.is_expired(current_sequence)
+    }
 }
 
+/// Whether a transaction with the given expiration sequence has expired by
+/// `current_sequence`. An expiration sequence of 0 never expires.
 #[napi]
-pub fn verify_transactions(serialized_transactions: Vec<Buffer>) -> bool {
-    let mut transactions: Vec<Transaction> = vec![];
+pub fn is_expired_sequence(expiration_sequence: u32, current_sequence: u32) -> bool {
+    transaction::is_expired_sequence(expiration_sequence, current_sequence)
+}
 
-    for tx_bytes in serialized_transactions {
-        match Transaction::read(&mut tx_bytes.as_ref()) {
-            Ok(tx) => transactions.push(tx),
-            Err(_) => return false,
+/// Default expiration sequence for a transaction created while the head of
+/// the chain is at `current_sequence`.
+#[napi]
+pub fn default_expiration_sequence(current_sequence: u32) -> u32 {
+    transaction::default_expiration_sequence(current_sequence)
+}
+
+/// Verify a batch of transactions, such as the transactions of a block. If
+/// any is invalid, the result is for the first invalid transaction and says
+/// which one it is.
+#[napi]
+pub fn verify_transactions(serialized_transactions: Vec<Buffer>) -> NativeVerificationResult {
+    verify_transactions_with_results(serialized_transactions)
+        .into_iter()
+        .find(|result| !result.valid)
+        .unwrap_or_else(|| NativeVerificationResult::new(Ok(()), None))
+}
+
+/// Verify a batch of transactions, returning a result for each one rather
+/// than a single result for the whole batch. Transactions that can't be
+/// deserialized are reported as invalid.
+#[napi]
+pub fn verify_transactions_with_results(
+    serialized_transactions: Vec<Buffer>,
+) -> Vec<NativeVerificationResult> {
+    let mut results: Vec<std::result::Result<(), IronfishError>> = vec![];
+    let mut transactions: Vec<Transaction> = vec![];
+    let mut indexes: Vec<usize> = vec![];
+
+    for (index, tx_bytes) in serialized_transactions.iter().enumerate() {
+        match Transaction::read_with_limits(tx_bytes.as_ref(), &TransactionLimits::default()) {
+            Ok(tx) => {
+                transactions.push(tx);
+                indexes.push(index);
+                results.push(Ok(()));
+            }
+            Err(e) => results.push(Err(e)),
         }
     }
 
-    batch_verify_transactions(transactions.iter()).is_ok()
+    let verified = batch_verify_transactions_with_results(transactions.iter());
+    for (index, result) in indexes.into_iter().zip(verified) {
+        results[index] = result;
+    }
+
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| NativeVerificationResult::new(result, Some(index as u32)))
+        .collect()
 }
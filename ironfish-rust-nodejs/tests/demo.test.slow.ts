@@ -51,7 +51,7 @@ describe('Demonstrate the Sapling API', () => {
     expect(postedTransaction.spendsLength()).toBe(0)
     expect(postedTransaction.hash().byteLength).toBe(32)
     expect(postedTransaction.transactionSignature().byteLength).toBe(64)
-    expect(postedTransaction.verify()).toBe(true)
+    expect(postedTransaction.verify().valid).toBe(true)
 
     const encryptedNote = new NoteEncrypted(postedTransaction.getNote(0))
     expect(encryptedNote.merkleHash().byteLength).toBe(32)
@@ -117,6 +117,6 @@ describe('Demonstrate the Sapling API', () => {
     expect(postedTransaction.spendsLength()).toEqual(1)
     expect(postedTransaction.hash().byteLength).toEqual(32)
     expect(postedTransaction.transactionSignature().byteLength).toEqual(64)
-    expect(postedTransaction.verify()).toBe(true)
+    expect(postedTransaction.verify().valid).toBe(true)
   })
 })
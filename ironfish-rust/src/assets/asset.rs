@@ -1,17 +1,18 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
-use crate::{errors::IronfishError, util::str_to_array, PublicAddress};
+use crate::{errors::IronfishError, util::str_to_array};
+use group::GroupEncoding;
 use ironfish_zkp::{
     constants::{
         ASSET_IDENTIFIER_LENGTH, ASSET_IDENTIFIER_PERSONALIZATION,
-        VALUE_COMMITMENT_GENERATOR_PERSONALIZATION,
+        VALUE_COMMITMENT_GENERATOR_PERSONALIZATION, VALUE_COMMITMENT_VALUE_GENERATOR,
     },
     group_hash,
 };
-use std::slice::from_ref;
+use jubjub::SubgroupPoint;
+use std::{io, slice::from_ref};
 
-#[allow(dead_code)]
 pub type AssetIdentifier = [u8; ASSET_IDENTIFIER_LENGTH];
 
 pub const NATIVE_ASSET: AssetIdentifier = [
@@ -19,38 +20,60 @@ pub const NATIVE_ASSET: AssetIdentifier = [
     253, 148, 34, 211, 177, 122, 246, 130, 58, 126, 106, 198,
 ];
 
+/// Get the generator used to commit to values of the given asset.
+///
+/// The native asset uses the original value commitment generator. Any other
+/// asset uses the point its identifier hashes to, which is the same generator
+/// the asset aware circuits derive. Minted and burned values only enter a
+/// transaction through its binding signature, so committing to them with the
+/// generator of their own asset is what keeps new supply of one asset from
+/// balancing notes of another.
+///
+/// Fails with [`IronfishError::InvalidAssetIdentifier`] if the identifier
+/// doesn't hash to a valid generator.
+pub fn value_commitment_generator(
+    asset_identifier: &AssetIdentifier,
+) -> Result<SubgroupPoint, IronfishError> {
+    if *asset_identifier == NATIVE_ASSET {
+        return Ok(VALUE_COMMITMENT_VALUE_GENERATOR);
+    }
+
+    group_hash(asset_identifier, VALUE_COMMITMENT_GENERATOR_PERSONALIZATION)
+        .ok_or(IronfishError::InvalidAssetIdentifier)
+}
+
 /// Describes all the fields necessary for creating and transacting with an
 /// asset on the Iron Fish network
-#[allow(dead_code)]
+#[derive(Clone, Copy)]
 pub struct Asset {
     /// Name of the asset
-    name: [u8; 32],
+    pub(crate) name: [u8; 32],
 
     /// Chain on the network the asset originated from (ex. Ropsten)
-    chain: [u8; 32],
+    pub(crate) chain: [u8; 32],
 
     /// Network the asset originated from (ex. Ethereum)
-    network: [u8; 32],
+    pub(crate) network: [u8; 32],
 
     /// Identifier field for bridged asset address, or if a native custom asset, random bytes.
-    token_identifier: [u8; 32],
+    pub(crate) token_identifier: [u8; 32],
 
-    /// The owner who created the asset. Has permissions to mint
-    owner: PublicAddress,
+    /// The asset public key of the owner who created the asset. Has
+    /// permissions to mint
+    pub(crate) owner: SubgroupPoint,
 
     /// The random byte used to ensure we get a valid asset identifier
-    nonce: u8,
+    pub(crate) nonce: u8,
 
     /// Unique byte array which is a hash of all of the identifying fields for
     /// an asset
-    identifier: AssetIdentifier,
+    pub(crate) identifier: AssetIdentifier,
 }
 
 impl Asset {
-    /// Create a new AssetType from a public address, name, chain, and network
-    #[allow(dead_code)]
+    /// Create a new AssetType from an asset public key, name, chain, and network
     pub fn new(
-        owner: PublicAddress,
+        owner: SubgroupPoint,
         name: &str,
         chain: &str,
         network: &str,
@@ -78,9 +101,8 @@ impl Asset {
         }
     }
 
-    #[allow(dead_code)]
     fn new_with_nonce(
-        owner: PublicAddress,
+        owner: SubgroupPoint,
         name: [u8; 32],
         chain: [u8; 32],
         network: [u8; 32],
@@ -95,7 +117,7 @@ impl Asset {
             .hash_length(ASSET_IDENTIFIER_LENGTH)
             .personal(ASSET_IDENTIFIER_PERSONALIZATION)
             .to_state()
-            .update(&owner.public_address())
+            .update(&owner.to_bytes())
             .update(&name)
             .update(&chain)
             .update(&network)
@@ -119,22 +141,67 @@ impl Asset {
         }
     }
 
-    #[allow(dead_code)]
+    /// Load an [`Asset`] from a Read implementation. The identifier is not
+    /// stored, but recomputed from the other fields.
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let owner = {
+            let mut bytes = [0; 32];
+            reader.read_exact(&mut bytes)?;
+
+            Option::from(SubgroupPoint::from_bytes(&bytes)).ok_or(IronfishError::InvalidData)?
+        };
+
+        let mut name = [0; 32];
+        reader.read_exact(&mut name)?;
+        let mut chain = [0; 32];
+        reader.read_exact(&mut chain)?;
+        let mut network = [0; 32];
+        reader.read_exact(&mut network)?;
+        let mut token_identifier = [0; 32];
+        reader.read_exact(&mut token_identifier)?;
+
+        let mut nonce = [0; 1];
+        reader.read_exact(&mut nonce)?;
+
+        Asset::new_with_nonce(owner, name, chain, network, token_identifier, nonce[0])
+    }
+
+    /// Stow the bytes of this [`Asset`] in the given writer.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_all(&self.owner.to_bytes())?;
+        writer.write_all(&self.name)?;
+        writer.write_all(&self.chain)?;
+        writer.write_all(&self.network)?;
+        writer.write_all(&self.token_identifier)?;
+        writer.write_all(from_ref(&self.nonce))?;
+
+        Ok(())
+    }
+
     pub fn name(&self) -> &[u8] {
         &self.name
     }
 
-    #[allow(dead_code)]
-    pub fn public_address(&self) -> &PublicAddress {
+    pub fn chain(&self) -> &[u8] {
+        &self.chain
+    }
+
+    pub fn network(&self) -> &[u8] {
+        &self.network
+    }
+
+    pub fn token_identifier(&self) -> &[u8] {
+        &self.token_identifier
+    }
+
+    pub fn owner(&self) -> &SubgroupPoint {
         &self.owner
     }
 
-    #[allow(dead_code)]
     pub fn nonce(&self) -> &u8 {
         &self.nonce
     }
 
-    #[allow(dead_code)]
     pub fn identifier(&self) -> &AssetIdentifier {
         &self.identifier
     }
@@ -143,27 +210,32 @@ impl Asset {
 #[cfg(test)]
 mod test {
     use group::GroupEncoding;
-    use ironfish_zkp::constants::VALUE_COMMITMENT_VALUE_GENERATOR;
+    use ironfish_zkp::{
+        constants::{
+            ASSET_IDENTIFIER_LENGTH, ASSET_IDENTIFIER_PERSONALIZATION, ASSET_KEY_GENERATOR,
+            VALUE_COMMITMENT_GENERATOR_PERSONALIZATION, VALUE_COMMITMENT_VALUE_GENERATOR,
+        },
+        group_hash,
+    };
+    use std::slice::from_ref;
 
-    use crate::{util::str_to_array, PublicAddress, SaplingKey};
+    use crate::{errors::IronfishError, util::str_to_array, SaplingKey};
 
-    use super::{Asset, NATIVE_ASSET};
+    use super::{value_commitment_generator, Asset, NATIVE_ASSET};
 
     #[test]
     fn test_asset_new_with_nonce() {
-        let owner = PublicAddress::new(&[
-            19, 26, 159, 204, 98, 253, 225, 73, 168, 125, 3, 240, 3, 129, 255, 146, 50, 134, 44,
-            84, 181, 195, 50, 249, 78, 128, 228, 152, 239, 10, 106, 10, 27, 58, 155, 162, 114, 133,
-            17, 48, 177, 29, 72,
-        ])
-        .expect("can create a deterministic public address");
+        let owner = ASSET_KEY_GENERATOR * jubjub::Fr::from(1234u64);
         let name = str_to_array("name");
         let chain = str_to_array("chain");
         let network = str_to_array("network");
         let token_identifier = str_to_array("token identifier");
-        let nonce = 0;
 
-        let asset = Asset::new_with_nonce(owner, name, chain, network, token_identifier, nonce)
+        // Not every nonce produces a valid identifier, so find one that does
+        let asset = (0..=u8::MAX)
+            .find_map(|nonce| {
+                Asset::new_with_nonce(owner, name, chain, network, token_identifier, nonce).ok()
+            })
             .expect("can create an asset");
 
         assert_eq!(asset.owner, owner);
@@ -171,20 +243,26 @@ mod test {
         assert_eq!(asset.chain, chain);
         assert_eq!(asset.network, network);
         assert_eq!(asset.token_identifier, token_identifier);
-        assert_eq!(asset.nonce, nonce);
-        assert_eq!(
-            asset.identifier,
-            [
-                174, 9, 19, 214, 96, 63, 10, 51, 94, 42, 41, 186, 207, 162, 48, 235, 1, 255, 211,
-                190, 228, 93, 137, 120, 138, 89, 61, 168, 168, 11, 150, 127
-            ],
-        );
+
+        // The identifier must match the preimage used by the MintAsset circuit
+        let identifier = blake2s_simd::Params::new()
+            .hash_length(ASSET_IDENTIFIER_LENGTH)
+            .personal(ASSET_IDENTIFIER_PERSONALIZATION)
+            .to_state()
+            .update(&owner.to_bytes())
+            .update(&name)
+            .update(&chain)
+            .update(&network)
+            .update(&token_identifier)
+            .update(from_ref(&asset.nonce))
+            .finalize();
+        assert_eq!(&asset.identifier, identifier.as_array());
     }
 
     #[test]
     fn test_asset_new() {
         let key = SaplingKey::generate_key();
-        let owner = key.generate_public_address();
+        let owner = key.asset_public_key();
         let name = "name";
         let chain = "chain";
         let network = "network";
@@ -200,10 +278,64 @@ mod test {
         assert_eq!(asset.token_identifier, str_to_array(token_identifier));
     }
 
+    #[test]
+    fn test_asset_round_trip() {
+        let key = SaplingKey::generate_key();
+        let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+            .expect("can create an asset");
+
+        let mut serialized_asset = vec![];
+        asset
+            .write(&mut serialized_asset)
+            .expect("should be able to serialize asset");
+        assert_eq!(serialized_asset.len(), 161);
+
+        let deserialized_asset =
+            Asset::read(&serialized_asset[..]).expect("should be able to deserialize asset");
+        assert_eq!(deserialized_asset.owner, asset.owner);
+        assert_eq!(deserialized_asset.name, asset.name);
+        assert_eq!(deserialized_asset.nonce, asset.nonce);
+        assert_eq!(deserialized_asset.identifier, asset.identifier);
+    }
+
     #[test]
     fn test_asset_native_identifier() {
         // Native asset uses the original value commitment generator, no
         // particular reason other than it is easier to think about this way.
         assert_eq!(NATIVE_ASSET, VALUE_COMMITMENT_VALUE_GENERATOR.to_bytes());
     }
+
+    #[test]
+    fn test_value_commitment_generator() {
+        assert_eq!(
+            value_commitment_generator(&NATIVE_ASSET).unwrap(),
+            VALUE_COMMITMENT_VALUE_GENERATOR
+        );
+
+        let key = SaplingKey::generate_key();
+        let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+            .expect("can create an asset");
+        let generator =
+            value_commitment_generator(asset.identifier()).expect("should have a generator");
+        assert_ne!(generator, VALUE_COMMITMENT_VALUE_GENERATOR);
+        assert_eq!(
+            Some(generator),
+            group_hash(
+                asset.identifier(),
+                VALUE_COMMITMENT_GENERATOR_PERSONALIZATION
+            )
+        );
+
+        // Not every identifier hashes to a point
+        let invalid_identifier = (0..=u8::MAX)
+            .map(|byte| [byte; 32])
+            .find(|identifier| {
+                group_hash(identifier, VALUE_COMMITMENT_GENERATOR_PERSONALIZATION).is_none()
+            })
+            .expect("should find an invalid identifier");
+        assert!(matches!(
+            value_commitment_generator(&invalid_identifier),
+            Err(IronfishError::InvalidAssetIdentifier)
+        ));
+    }
 }
@@ -0,0 +1,338 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Bech32m encoding (BIP-350), used to give keys and addresses a
+//! human-readable prefix and a checksum that catches typos.
+//!
+//! The prefix names the kind of key and the network it's meant for, so a
+//! testnet address can't be mistaken for a mainnet one.
+
+use crate::errors::{Bech32Error, IronfishError};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const SEPARATOR: char = '1';
+const CHECKSUM_LENGTH: usize = 6;
+const BECH32M_CONST: u32 = 0x2bc830a3;
+
+/// Longest string allowed by the spec. The checksum is only guaranteed to
+/// detect errors up to this length.
+const MAX_LENGTH: usize = 90;
+
+/// Network a key or address is encoded for. Each network has its own set of
+/// human-readable prefixes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    pub fn public_address_prefix(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "ifaddr",
+            Network::Testnet => "tifaddr",
+        }
+    }
+
+    pub fn incoming_view_key_prefix(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "ifivk",
+            Network::Testnet => "tifivk",
+        }
+    }
+
+    pub fn outgoing_view_key_prefix(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "ifovk",
+            Network::Testnet => "tifovk",
+        }
+    }
+
+    pub fn spending_key_prefix(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "ifsk",
+            Network::Testnet => "tifsk",
+        }
+    }
+}
+
+/// Encode `data` as a Bech32m string with the given human-readable prefix.
+/// The prefix is expected to be lowercase ASCII.
+pub fn encode(prefix: &str, data: &[u8]) -> String {
+    let mut values = convert_bits(data, 8, 5, true).expect("padding is allowed when encoding");
+    let checksum = create_checksum(prefix, &values);
+    values.extend_from_slice(&checksum);
+
+    let mut encoded = String::with_capacity(prefix.len() + 1 + values.len());
+    encoded.push_str(prefix);
+    encoded.push(SEPARATOR);
+    for value in values {
+        encoded.push(CHARSET[value as usize] as char);
+    }
+
+    encoded
+}
+
+/// Decode a Bech32m string, checking that it has the `expected_prefix`.
+pub fn decode_with_prefix(value: &str, expected_prefix: &str) -> Result<Vec<u8>, IronfishError> {
+    let (prefix, data) = decode(value)?;
+    if prefix != expected_prefix {
+        return Err(IronfishError::InvalidBech32(Bech32Error::WrongPrefix));
+    }
+
+    Ok(data)
+}
+
+/// Decode a Bech32m string into its human-readable prefix and data.
+///
+/// Errors include the position of the offending character where it can be
+/// found. That includes checksum failures caused by a single mistyped
+/// character, which are the most common kind of mistake.
+pub fn decode(value: &str) -> Result<(String, Vec<u8>), IronfishError> {
+    if value.len() > MAX_LENGTH {
+        return Err(invalid(Bech32Error::InvalidLength));
+    }
+
+    let mut first_case = None;
+    for (position, character) in value.char_indices() {
+        if !('!'..='~').contains(&character) {
+            return Err(invalid(Bech32Error::InvalidCharacter(position)));
+        }
+        if character.is_ascii_alphabetic() {
+            let is_upper = character.is_ascii_uppercase();
+            match first_case {
+                None => first_case = Some(is_upper),
+                Some(first_is_upper) if first_is_upper != is_upper => {
+                    return Err(invalid(Bech32Error::MixedCase(position)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let value = value.to_ascii_lowercase();
+    let separator = value
+        .rfind(SEPARATOR)
+        .ok_or_else(|| invalid(Bech32Error::MissingSeparator))?;
+    if separator == 0 {
+        return Err(invalid(Bech32Error::WrongPrefix));
+    }
+    if separator + 1 + CHECKSUM_LENGTH > value.len() {
+        return Err(invalid(Bech32Error::InvalidLength));
+    }
+
+    let prefix = &value[..separator];
+    let mut values = Vec::with_capacity(value.len() - separator - 1);
+    for (offset, character) in value[separator + 1..].bytes().enumerate() {
+        let position = separator + 1 + offset;
+        let index = CHARSET
+            .iter()
+            .position(|c| *c == character)
+            .ok_or_else(|| invalid(Bech32Error::InvalidCharacter(position)))?;
+        values.push(index as u8);
+    }
+
+    if !verify_checksum(prefix, &values) {
+        let position =
+            locate_substitution(prefix, &mut values).map(|offset| separator + 1 + offset);
+        return Err(invalid(Bech32Error::InvalidChecksum(position)));
+    }
+
+    values.truncate(values.len() - CHECKSUM_LENGTH);
+    let data =
+        convert_bits(&values, 5, 8, false).ok_or_else(|| invalid(Bech32Error::InvalidPadding))?;
+
+    Ok((prefix.to_string(), data))
+}
+
+fn invalid(error: Bech32Error) -> IronfishError {
+    IronfishError::InvalidBech32(error)
+}
+
+/// Find the single character that, when replaced, makes the checksum valid.
+/// Returns its offset into `values`, or `None` if there isn't exactly one
+/// such character.
+fn locate_substitution(prefix: &str, values: &mut [u8]) -> Option<usize> {
+    let mut found = None;
+
+    for offset in 0..values.len() {
+        let original = values[offset];
+        for candidate in 0..32 {
+            if candidate == original {
+                continue;
+            }
+
+            values[offset] = candidate;
+            if verify_checksum(prefix, values) {
+                if found.is_some() {
+                    values[offset] = original;
+                    return None;
+                }
+                found = Some(offset);
+            }
+        }
+        values[offset] = original;
+    }
+
+    found
+}
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+
+    checksum
+}
+
+/// The prefix is mixed into the checksum, so data can't be moved to another
+/// prefix without invalidating it.
+fn expand_prefix(prefix: &str) -> impl Iterator<Item = u8> + '_ {
+    prefix
+        .bytes()
+        .map(|c| c >> 5)
+        .chain(std::iter::once(0))
+        .chain(prefix.bytes().map(|c| c & 31))
+}
+
+fn verify_checksum(prefix: &str, values: &[u8]) -> bool {
+    polymod(expand_prefix(prefix).chain(values.iter().copied())) == BECH32M_CONST
+}
+
+fn create_checksum(prefix: &str, values: &[u8]) -> [u8; CHECKSUM_LENGTH] {
+    let remainder = polymod(
+        expand_prefix(prefix)
+            .chain(values.iter().copied())
+            .chain([0; CHECKSUM_LENGTH]),
+    ) ^ BECH32M_CONST;
+
+    let mut checksum = [0; CHECKSUM_LENGTH];
+    for (i, value) in checksum.iter_mut().enumerate() {
+        *value = ((remainder >> (5 * (5 - i))) & 31) as u8;
+    }
+
+    checksum
+}
+
+/// Regroup `data` from `from` bit values into `to` bit values. Returns `None`
+/// if `pad` is false and there are leftover non-zero bits, or more than a
+/// group of leftover bits.
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut accumulator: u32 = 0;
+    let mut bits = 0;
+    let max_value = (1 << to) - 1;
+    let mut converted = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+    for value in data {
+        accumulator = (accumulator << from) | *value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            converted.push(((accumulator >> bits) & max_value) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            converted.push(((accumulator << (to - bits)) & max_value) as u8);
+        }
+    } else if bits >= from || (accumulator << (to - bits)) & max_value != 0 {
+        return None;
+    }
+
+    Some(converted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, decode_with_prefix, encode};
+    use crate::errors::{Bech32Error, IronfishError};
+
+    #[test]
+    fn test_valid_strings() {
+        // Test vectors from BIP-350
+        let valid = [
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11sg7hg6",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ];
+
+        for value in valid {
+            let (prefix, _) = decode(value).expect("should decode valid string");
+            assert_eq!(prefix, value[..value.rfind('1').unwrap()].to_lowercase());
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data: Vec<u8> = (0..10).collect();
+        let encoded = encode("test", &data);
+        assert_eq!(encoded, "test1qqqsyqcyq5rqwzqfrv83f0");
+
+        let (prefix, decoded) = decode(&encoded).expect("should decode encoded string");
+        assert_eq!(prefix, "test");
+        assert_eq!(decoded, data);
+
+        let decoded = decode(&encoded.to_uppercase()).expect("should decode uppercase string");
+        assert_eq!(decoded.1, data);
+
+        assert!(matches!(
+            decode_with_prefix(&encoded, "other"),
+            Err(IronfishError::InvalidBech32(Bech32Error::WrongPrefix))
+        ));
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let encoded = encode("test", &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        // Replace one character of the data with another valid one
+        let mut mistyped = encoded.clone().into_bytes();
+        mistyped[9] = if mistyped[9] == b'q' { b'p' } else { b'q' };
+        let mistyped = String::from_utf8(mistyped).unwrap();
+        assert!(matches!(
+            decode(&mistyped),
+            Err(IronfishError::InvalidBech32(Bech32Error::InvalidChecksum(
+                Some(9)
+            )))
+        ));
+
+        let mut invalid_character = encoded.clone();
+        invalid_character.replace_range(7..8, "b");
+        assert!(matches!(
+            decode(&invalid_character),
+            Err(IronfishError::InvalidBech32(Bech32Error::InvalidCharacter(
+                7
+            )))
+        ));
+
+        let mut mixed_case = encoded.clone();
+        mixed_case.replace_range(6..7, &encoded[6..7].to_uppercase());
+        assert!(matches!(
+            decode(&mixed_case),
+            Err(IronfishError::InvalidBech32(Bech32Error::MixedCase(_)))
+        ));
+
+        assert!(matches!(
+            decode("testqqqsyqcyq5rqwzqfrv83f0"),
+            Err(IronfishError::InvalidBech32(Bech32Error::MissingSeparator))
+        ));
+        assert!(matches!(
+            decode("test1qqqqq"),
+            Err(IronfishError::InvalidBech32(Bech32Error::InvalidLength))
+        ));
+    }
+}
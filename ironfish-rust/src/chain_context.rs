@@ -0,0 +1,24 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use bls12_381::Scalar;
+use ironfish_zkp::Nullifier;
+
+/// View of the chain a transaction is being added to, used to check the parts
+/// of a transaction that depend on chain state.
+///
+/// Implemented by whatever holds that state, such as the node's database, and
+/// passed to [`crate::Transaction::verify_contextual`].
+pub trait ChainContext {
+    /// Whether the note commitment tree had the given root hash when it
+    /// contained `tree_size` notes.
+    fn is_known_anchor(&self, root_hash: &Scalar, tree_size: u32) -> bool;
+
+    /// Whether a note with the given nullifier has already been spent on the
+    /// chain.
+    fn is_nullifier_spent(&self, nullifier: &Nullifier) -> bool;
+
+    /// Sequence of the block the transaction is being added to.
+    fn current_sequence(&self) -> u32;
+}
@@ -17,10 +17,12 @@ pub enum IronfishError {
     BellmanSynthesis(bellman::SynthesisError),
     BellmanVerification(bellman::VerificationError),
     CryptoBox(crypto_box::aead::Error),
+    DuplicateNullifier(DuplicateNullifier),
     IllegalValue,
     InconsistentWitness,
     InvalidAssetIdentifier,
     InvalidBalance,
+    InvalidBech32(Bech32Error),
     InvalidCommitment,
     InvalidData,
     InvalidDecryptionKey,
@@ -28,18 +30,114 @@ pub enum IronfishError {
     InvalidLanguageEncoding,
     InvalidMinersFeeTransaction,
     InvalidNonceLength,
+    InvalidParameters,
     InvalidPaymentAddress,
+    InvalidPaymentRequest,
     InvalidPublicAddress,
+    InvalidSeed,
     InvalidSigningKey,
+    InvalidTransaction(VerificationCheck),
     InvalidViewingKey,
     InvalidWord,
     Io(io::Error),
     IsSmallOrder,
+    MissingProvingParameters,
     RandomnessError,
+    SaplingAlreadyLoaded,
+    ThreadPool(rayon::ThreadPoolBuildError),
+    TrailingBytes,
+    TransactionTooLarge,
+    UnsupportedAsset,
+    UnsupportedVersion,
     Utf8(string::FromUtf8Error),
     VerificationFailed,
 }
 
+/// Why a Bech32m string could not be decoded. Positions are offsets of the
+/// offending character in the string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bech32Error {
+    /// The checksum doesn't match. Includes the position of the mistyped
+    /// character when a single one can be found.
+    InvalidChecksum(Option<usize>),
+    InvalidCharacter(usize),
+    InvalidLength,
+    InvalidPadding,
+    MissingSeparator,
+    MixedCase(usize),
+    WrongPrefix,
+}
+
+/// Two spends of the same note. Each spend is identified by the index of its
+/// transaction in the batch, and its index within that transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DuplicateNullifier {
+    /// The spend that used the nullifier first
+    pub first: (usize, usize),
+
+    /// The spend that used the nullifier again
+    pub second: (usize, usize),
+}
+
+/// The check that failed when verifying a transaction, with the index of the
+/// spend, output, mint, or burn it failed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationCheck {
+    BindingSignature,
+    BurnValue(usize),
+    Expired,
+    MintProof(usize),
+    MintSignature(usize),
+    MintValue(usize),
+    OutputProof(usize),
+    OutputValueCommitment(usize),
+    SpendAnchor(usize),
+    SpendNullifier(usize),
+    SpendProof(usize),
+    SpendSignature(usize),
+    SpendValueCommitment(usize),
+}
+
+impl VerificationCheck {
+    /// Name of the check, without the index
+    pub fn name(&self) -> &'static str {
+        match self {
+            VerificationCheck::BindingSignature => "BindingSignature",
+            VerificationCheck::BurnValue(_) => "BurnValue",
+            VerificationCheck::Expired => "Expired",
+            VerificationCheck::MintProof(_) => "MintProof",
+            VerificationCheck::MintSignature(_) => "MintSignature",
+            VerificationCheck::MintValue(_) => "MintValue",
+            VerificationCheck::OutputProof(_) => "OutputProof",
+            VerificationCheck::OutputValueCommitment(_) => "OutputValueCommitment",
+            VerificationCheck::SpendAnchor(_) => "SpendAnchor",
+            VerificationCheck::SpendNullifier(_) => "SpendNullifier",
+            VerificationCheck::SpendProof(_) => "SpendProof",
+            VerificationCheck::SpendSignature(_) => "SpendSignature",
+            VerificationCheck::SpendValueCommitment(_) => "SpendValueCommitment",
+        }
+    }
+
+    /// Index of the spend, output, mint, or burn the check failed on. None
+    /// for checks of the transaction as a whole.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            VerificationCheck::BindingSignature | VerificationCheck::Expired => None,
+            VerificationCheck::BurnValue(index)
+            | VerificationCheck::MintProof(index)
+            | VerificationCheck::MintSignature(index)
+            | VerificationCheck::MintValue(index)
+            | VerificationCheck::OutputProof(index)
+            | VerificationCheck::OutputValueCommitment(index)
+            | VerificationCheck::SpendAnchor(index)
+            | VerificationCheck::SpendNullifier(index)
+            | VerificationCheck::SpendProof(index)
+            | VerificationCheck::SpendSignature(index)
+            | VerificationCheck::SpendValueCommitment(index) => Some(*index),
+        }
+    }
+}
+
 impl Error for IronfishError {}
 
 impl fmt::Display for IronfishError {
@@ -72,6 +170,12 @@ impl From<bellman::VerificationError> for IronfishError {
     }
 }
 
+impl From<rayon::ThreadPoolBuildError> for IronfishError {
+    fn from(e: rayon::ThreadPoolBuildError) -> IronfishError {
+        IronfishError::ThreadPool(e)
+    }
+}
+
 impl From<bellman::SynthesisError> for IronfishError {
     fn from(e: bellman::SynthesisError) -> IronfishError {
         IronfishError::BellmanSynthesis(e)
@@ -0,0 +1,217 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    errors::IronfishError,
+    serializing::{bytes_to_hex, hex_to_bytes},
+};
+
+use super::SaplingKey;
+use blake2b_simd::Params as Blake2b;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use group::GroupEncoding;
+
+use std::io;
+
+const MASTER_KEY_PERSONALIZATION: &[u8; 16] = b"Ironfish HD seed";
+const CHILD_KEY_PERSONALIZATION: &[u8; 16] = b"Ironfish HD chld";
+const FINGERPRINT_PERSONALIZATION: &[u8; 16] = b"Ironfish HD fgpt";
+
+/// Seeds shorter than this don't have enough entropy to derive keys from.
+pub const MIN_SEED_LENGTH: usize = 32;
+
+/// Longest seed that can be used to derive a master key.
+pub const MAX_SEED_LENGTH: usize = 252;
+
+/// Length of a serialized [`ExtendedSpendingKey`].
+pub const EXTENDED_SPENDING_KEY_LENGTH: usize = 1 + 4 + 4 + 32 + 32;
+
+/// A [`SaplingKey`] that can derive child keys, in the style of ZIP-32.
+///
+/// A master key is derived from a seed, and every other key is derived from
+/// its parent and an index, so a single seed can hold any number of accounts.
+/// All derivation is hardened: the child's spending key is computed from the
+/// parent's spending key, so a child key can't be used to recover its parent
+/// or siblings.
+#[derive(Clone)]
+pub struct ExtendedSpendingKey {
+    /// Number of derivations between the master key and this key. The master
+    /// key has a depth of 0.
+    depth: u8,
+
+    /// First bytes of the fingerprint of the parent key, to help tell keys
+    /// apart. All zeroes for the master key.
+    parent_fingerprint: [u8; 4],
+
+    /// Index this key was derived at from its parent. 0 for the master key.
+    child_index: u32,
+
+    /// Extra entropy mixed into the derivation of every child key, so
+    /// knowing the spending key alone isn't enough to derive children.
+    chain_code: [u8; 32],
+
+    /// The key used to spend and view notes.
+    key: SaplingKey,
+}
+
+impl ExtendedSpendingKey {
+    /// Derive the master key from a seed, eg: the entropy of a mnemonic.
+    pub fn master(seed: &[u8]) -> Result<Self, IronfishError> {
+        if seed.len() < MIN_SEED_LENGTH || seed.len() > MAX_SEED_LENGTH {
+            return Err(IronfishError::InvalidSeed);
+        }
+
+        let hash = Blake2b::new()
+            .hash_length(64)
+            .personal(MASTER_KEY_PERSONALIZATION)
+            .hash(seed);
+
+        Self::from_hash(hash.as_bytes(), 0, [0; 4], 0)
+    }
+
+    /// Derive the child key at `index`.
+    ///
+    /// This fails in the vanishingly unlikely case that the derived spending
+    /// key is not a valid [`SaplingKey`]. Callers should move on to the next
+    /// index when that happens.
+    pub fn derive_child(&self, index: u32) -> Result<Self, IronfishError> {
+        let depth = self
+            .depth
+            .checked_add(1)
+            .ok_or(IronfishError::IllegalValue)?;
+
+        let hash = Blake2b::new()
+            .hash_length(64)
+            .key(&self.chain_code)
+            .personal(CHILD_KEY_PERSONALIZATION)
+            .to_state()
+            .update(&self.key.spending_key())
+            .update(&index.to_le_bytes())
+            .finalize();
+
+        let mut parent_fingerprint = [0; 4];
+        parent_fingerprint.copy_from_slice(&self.fingerprint()[..4]);
+
+        Self::from_hash(hash.as_bytes(), depth, parent_fingerprint, index)
+    }
+
+    /// Derive the key at the end of `path`, one child at a time starting
+    /// from this key.
+    pub fn derive_path(&self, path: &[u32]) -> Result<Self, IronfishError> {
+        let mut key = self.clone();
+        for index in path {
+            key = key.derive_child(*index)?;
+        }
+
+        Ok(key)
+    }
+
+    /// Identifies this key without revealing anything about it. Computed from
+    /// the public parts of the key, so a holder of the view keys can compute
+    /// it as well.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut fingerprint = [0; 32];
+        fingerprint.copy_from_slice(
+            Blake2b::new()
+                .hash_length(32)
+                .personal(FINGERPRINT_PERSONALIZATION)
+                .to_state()
+                .update(&self.key.authorizing_key.to_bytes())
+                .update(&self.key.nullifier_deriving_key.to_bytes())
+                .update(&self.key.outgoing_viewing_key.view_key)
+                .finalize()
+                .as_bytes(),
+        );
+
+        fingerprint
+    }
+
+    /// Load an extended key from a Read implementation (e.g: socket, file)
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let depth = reader.read_u8()?;
+        let mut parent_fingerprint = [0; 4];
+        reader.read_exact(&mut parent_fingerprint)?;
+        let child_index = reader.read_u32::<LittleEndian>()?;
+        let mut chain_code = [0; 32];
+        reader.read_exact(&mut chain_code)?;
+        let key = SaplingKey::read(&mut reader)?;
+
+        Ok(ExtendedSpendingKey {
+            depth,
+            parent_fingerprint,
+            child_index,
+            chain_code,
+            key,
+        })
+    }
+
+    /// Store the bytes of this extended key in the given writer.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_u8(self.depth)?;
+        writer.write_all(&self.parent_fingerprint)?;
+        writer.write_u32::<LittleEndian>(self.child_index)?;
+        writer.write_all(&self.chain_code)?;
+        self.key.write(&mut writer)?;
+
+        Ok(())
+    }
+
+    /// Load an extended key from a string of hexadecimal digits
+    pub fn from_hex(value: &str) -> Result<Self, IronfishError> {
+        let bytes = hex_to_bytes(value).map_err(|_| IronfishError::InvalidData)?;
+        if bytes.len() != EXTENDED_SPENDING_KEY_LENGTH {
+            return Err(IronfishError::InvalidData);
+        }
+
+        Self::read(&bytes[..])
+    }
+
+    /// Extended key as hexadecimal
+    pub fn hex_key(&self) -> String {
+        let mut bytes = Vec::with_capacity(EXTENDED_SPENDING_KEY_LENGTH);
+        self.write(&mut bytes)
+            .expect("writing to a vec should not fail");
+
+        bytes_to_hex(&bytes)
+    }
+
+    /// Retrieve the key used to spend and view notes
+    pub fn sapling_key(&self) -> &SaplingKey {
+        &self.key
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn parent_fingerprint(&self) -> [u8; 4] {
+        self.parent_fingerprint
+    }
+
+    pub fn child_index(&self) -> u32 {
+        self.child_index
+    }
+
+    /// Split a 64 byte derivation hash into the spending key and chain code
+    /// of a new extended key.
+    fn from_hash(
+        hash: &[u8],
+        depth: u8,
+        parent_fingerprint: [u8; 4],
+        child_index: u32,
+    ) -> Result<Self, IronfishError> {
+        let mut spending_key = [0; 32];
+        spending_key.copy_from_slice(&hash[..32]);
+        let mut chain_code = [0; 32];
+        chain_code.copy_from_slice(&hash[32..64]);
+
+        Ok(ExtendedSpendingKey {
+            depth,
+            parent_fingerprint,
+            child_index,
+            chain_code,
+            key: SaplingKey::new(spending_key)?,
+        })
+    }
+}
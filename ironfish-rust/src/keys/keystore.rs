@@ -0,0 +1,282 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    errors::IronfishError,
+    serializing::{aead::MAC_SIZE, bytes_to_hex, hex_to_bytes},
+};
+
+use super::{PublicAddress, SaplingKey};
+use blake2b_simd::Params as Blake2b;
+use byteorder::{LittleEndian, ReadBytesExt};
+use crypto::{
+    aead::{AeadDecryptor, AeadEncryptor},
+    chacha20poly1305::ChaCha20Poly1305,
+    scrypt::{scrypt, ScryptParams},
+};
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use std::io;
+
+const ADDRESS_FINGERPRINT_PERSONALIZATION: &[u8; 16] = b"Ironfish KS addr";
+
+/// Current version of the [`EncryptedSpendingKey`] format.
+pub const KEYSTORE_VERSION: u8 = 1;
+
+/// Most memory the KDF is allowed to use when importing a key. Keeps a
+/// crafted keystore from exhausting the memory of whoever opens it.
+pub const MAX_KDF_MEMORY: u64 = 1 << 30;
+
+const KDF_PARAMS_LENGTH: usize = 1 + 4 + 4;
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 8;
+const FINGERPRINT_LENGTH: usize = 32;
+const ENCRYPTED_KEY_LENGTH: usize = 32 + MAC_SIZE;
+
+/// Length of a serialized [`EncryptedSpendingKey`].
+pub const ENCRYPTED_SPENDING_KEY_LENGTH: usize =
+    1 + KDF_PARAMS_LENGTH + SALT_LENGTH + NONCE_LENGTH + FINGERPRINT_LENGTH + ENCRYPTED_KEY_LENGTH;
+
+/// Cost parameters of the scrypt function used to derive the encryption key
+/// from a password.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Base 2 logarithm of the CPU and memory cost
+    pub log_n: u8,
+
+    /// Block size
+    pub r: u32,
+
+    /// Parallelization
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    /// Uses about 32 MiB of memory, which takes a fraction of a second on
+    /// a desktop machine.
+    fn default() -> Self {
+        KdfParams {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn validate(&self) -> Result<(), IronfishError> {
+        if self.log_n == 0 || self.log_n > 24 || self.r == 0 || self.r > 32 {
+            return Err(IronfishError::InvalidData);
+        }
+        if self.p == 0 || self.p > 16 {
+            return Err(IronfishError::InvalidData);
+        }
+        if (128 * self.r as u64) << self.log_n > MAX_KDF_MEMORY {
+            return Err(IronfishError::InvalidData);
+        }
+        // Required by scrypt itself; rust-crypto panics on parameters that
+        // don't meet these
+        if self.log_n as u64 >= 16 * self.r as u64 || self.r as u64 * self.p as u64 >= 1 << 30 {
+            return Err(IronfishError::InvalidData);
+        }
+
+        Ok(())
+    }
+
+    fn derive_key(&self, password: &str, salt: &[u8]) -> Result<[u8; 32], IronfishError> {
+        self.validate()?;
+
+        let mut key = [0; 32];
+        let params = ScryptParams::new(self.log_n, self.r, self.p);
+        scrypt(password.as_bytes(), salt, &params, &mut key);
+
+        Ok(key)
+    }
+}
+
+/// A spending key encrypted with a password, for storing keys at rest.
+///
+/// The key is encrypted with ChaCha20Poly1305, using a key derived from the
+/// password with scrypt. Everything else in the keystore is authenticated as
+/// additional data, so it can't be tampered with to weaken the KDF.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedSpendingKey {
+    version: u8,
+    kdf_params: KdfParams,
+    salt: [u8; SALT_LENGTH],
+    nonce: [u8; NONCE_LENGTH],
+
+    /// Fingerprint of the default address of the encrypted key, so a wallet
+    /// can tell which account a keystore belongs to without the password.
+    address_fingerprint: [u8; FINGERPRINT_LENGTH],
+
+    /// Encrypted spending key followed by the MAC
+    encrypted_key: [u8; ENCRYPTED_KEY_LENGTH],
+}
+
+impl EncryptedSpendingKey {
+    /// Encrypt `key` with `password`, using the default KDF parameters.
+    pub fn encrypt(key: &SaplingKey, password: &str) -> Result<Self, IronfishError> {
+        Self::encrypt_with_rng(key, password, KdfParams::default(), &mut thread_rng())
+    }
+
+    /// Same as [`EncryptedSpendingKey::encrypt`], but with the given KDF
+    /// parameters and drawing the salt and nonce from the given rng.
+    pub fn encrypt_with_rng<R: RngCore + CryptoRng>(
+        key: &SaplingKey,
+        password: &str,
+        kdf_params: KdfParams,
+        rng: &mut R,
+    ) -> Result<Self, IronfishError> {
+        let mut salt = [0; SALT_LENGTH];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0; NONCE_LENGTH];
+        rng.fill_bytes(&mut nonce);
+
+        let mut keystore = EncryptedSpendingKey {
+            version: KEYSTORE_VERSION,
+            kdf_params,
+            salt,
+            nonce,
+            address_fingerprint: Self::fingerprint_address(&key.generate_public_address()),
+            encrypted_key: [0; ENCRYPTED_KEY_LENGTH],
+        };
+
+        let encryption_key = kdf_params.derive_key(password, &salt)?;
+        let mut encryptor =
+            ChaCha20Poly1305::new(&encryption_key, &nonce, &keystore.additional_data());
+        let mut tag = [0; MAC_SIZE];
+        encryptor.encrypt(
+            &key.spending_key,
+            &mut keystore.encrypted_key[..32],
+            &mut tag,
+        );
+        keystore.encrypted_key[32..].copy_from_slice(&tag);
+
+        Ok(keystore)
+    }
+
+    /// Decrypt the spending key with `password`. Fails with
+    /// [`IronfishError::InvalidDecryptionKey`] if the password is wrong.
+    pub fn decrypt(&self, password: &str) -> Result<SaplingKey, IronfishError> {
+        let encryption_key = self.kdf_params.derive_key(password, &self.salt)?;
+        let mut decryptor =
+            ChaCha20Poly1305::new(&encryption_key, &self.nonce, &self.additional_data());
+        let mut spending_key = [0; 32];
+        if !decryptor.decrypt(
+            &self.encrypted_key[..32],
+            &mut spending_key,
+            &self.encrypted_key[32..],
+        ) {
+            return Err(IronfishError::InvalidDecryptionKey);
+        }
+
+        let key = SaplingKey::new(spending_key)?;
+        if Self::fingerprint_address(&key.generate_public_address()) != self.address_fingerprint {
+            return Err(IronfishError::InvalidData);
+        }
+
+        Ok(key)
+    }
+
+    /// Fingerprint of a public address, as stored in a keystore for the key
+    /// whose default address it is.
+    pub fn fingerprint_address(address: &PublicAddress) -> [u8; FINGERPRINT_LENGTH] {
+        let mut fingerprint = [0; FINGERPRINT_LENGTH];
+        fingerprint.copy_from_slice(
+            Blake2b::new()
+                .hash_length(FINGERPRINT_LENGTH)
+                .personal(ADDRESS_FINGERPRINT_PERSONALIZATION)
+                .hash(&address.public_address())
+                .as_bytes(),
+        );
+
+        fingerprint
+    }
+
+    /// Load a keystore from a Read implementation (e.g: socket, file)
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let version = reader.read_u8()?;
+        if version != KEYSTORE_VERSION {
+            return Err(IronfishError::UnsupportedVersion);
+        }
+
+        let log_n = reader.read_u8()?;
+        let r = reader.read_u32::<LittleEndian>()?;
+        let p = reader.read_u32::<LittleEndian>()?;
+        let kdf_params = KdfParams { log_n, r, p };
+        kdf_params.validate()?;
+
+        let mut salt = [0; SALT_LENGTH];
+        reader.read_exact(&mut salt)?;
+        let mut nonce = [0; NONCE_LENGTH];
+        reader.read_exact(&mut nonce)?;
+        let mut address_fingerprint = [0; FINGERPRINT_LENGTH];
+        reader.read_exact(&mut address_fingerprint)?;
+        let mut encrypted_key = [0; ENCRYPTED_KEY_LENGTH];
+        reader.read_exact(&mut encrypted_key)?;
+
+        Ok(EncryptedSpendingKey {
+            version,
+            kdf_params,
+            salt,
+            nonce,
+            address_fingerprint,
+            encrypted_key,
+        })
+    }
+
+    /// Store the bytes of this keystore in the given writer.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_all(&self.additional_data())?;
+        writer.write_all(&self.encrypted_key)?;
+
+        Ok(())
+    }
+
+    /// Load a keystore from a string of hexadecimal digits
+    pub fn from_hex(value: &str) -> Result<Self, IronfishError> {
+        let bytes = hex_to_bytes(value).map_err(|_| IronfishError::InvalidData)?;
+        if bytes.len() != ENCRYPTED_SPENDING_KEY_LENGTH {
+            return Err(IronfishError::InvalidData);
+        }
+
+        Self::read(&bytes[..])
+    }
+
+    /// Keystore as hexadecimal
+    pub fn hex_keystore(&self) -> String {
+        let mut bytes = Vec::with_capacity(ENCRYPTED_SPENDING_KEY_LENGTH);
+        self.write(&mut bytes)
+            .expect("writing to a vec should not fail");
+
+        bytes_to_hex(&bytes)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf_params
+    }
+
+    pub fn address_fingerprint(&self) -> [u8; FINGERPRINT_LENGTH] {
+        self.address_fingerprint
+    }
+
+    /// Every field except the encrypted key, in serialized form.
+    fn additional_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(ENCRYPTED_SPENDING_KEY_LENGTH - ENCRYPTED_KEY_LENGTH);
+        data.push(self.version);
+        data.push(self.kdf_params.log_n);
+        data.extend_from_slice(&self.kdf_params.r.to_le_bytes());
+        data.extend_from_slice(&self.kdf_params.p.to_le_bytes());
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&self.nonce);
+        data.extend_from_slice(&self.address_fingerprint);
+
+        data
+    }
+}
@@ -2,7 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::errors::IronfishError;
+use crate::{
+    bech32m::{self, Network},
+    errors::IronfishError,
+};
 
 use super::serializing::{
     bytes_to_hex, hex_to_bytes, point_to_bytes, read_scalar, scalar_to_bytes,
@@ -21,6 +24,10 @@ use rand::prelude::*;
 
 use std::io;
 
+mod extended_key;
+pub use extended_key::*;
+mod keystore;
+pub use keystore::*;
 mod public_address;
 pub use public_address::*;
 mod view_keys;
@@ -146,6 +153,16 @@ impl SaplingKey {
         }
     }
 
+    /// Load a key from its Bech32m form for the given network.
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes = bech32m::decode_with_prefix(value, network.spending_key_prefix())?;
+        let spending_key = bytes
+            .try_into()
+            .map_err(|_| IronfishError::InvalidSigningKey)?;
+
+        Self::new(spending_key)
+    }
+
     /// Load a key from a string of words to be decoded into bytes.
     pub fn from_words(language_code: &str, value: String) -> Result<Self, IronfishError> {
         let language = Language::from_language_code(language_code)
@@ -183,15 +200,30 @@ impl SaplingKey {
         PublicAddress::from_key(self, diversifier)
     }
 
-    /// Generate a public address from this key's incoming viewing key,
-    /// picking a diversifier that is guaranteed to work with it.
+    /// Generate the default public address of this key's incoming viewing
+    /// key. See [`IncomingViewKey::generate_public_address`].
     ///
-    /// This method always succeeds, retrying with a different diversifier if
-    /// one doesn't work
+    /// This method always succeeds, and always returns the same address for
+    /// the same key.
     pub fn generate_public_address(&self) -> PublicAddress {
         self.incoming_viewing_key.generate_public_address()
     }
 
+    /// Generate the public address with the diversifier at `index`. See
+    /// [`IncomingViewKey::address_at`].
+    pub fn address_at(&self, index: u64) -> Result<PublicAddress, IronfishError> {
+        self.incoming_viewing_key.address_at(index)
+    }
+
+    /// Find the first index at or after `from_index` with a valid address.
+    /// See [`IncomingViewKey::next_valid_address`].
+    pub fn next_valid_address(
+        &self,
+        from_index: u64,
+    ) -> Result<(u64, PublicAddress), IronfishError> {
+        self.incoming_viewing_key.next_valid_address(from_index)
+    }
+
     // Write a bytes representation of this key to the provided stream
     pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
         let num_bytes_written = writer.write(&self.spending_key)?;
@@ -202,6 +234,20 @@ impl SaplingKey {
         Ok(())
     }
 
+    /// Encrypt the spending key with a password, for storing it at rest.
+    /// See [`EncryptedSpendingKey`].
+    pub fn export_encrypted(&self, password: &str) -> Result<EncryptedSpendingKey, IronfishError> {
+        EncryptedSpendingKey::encrypt(self, password)
+    }
+
+    /// Load a key from a keystore created by [`SaplingKey::export_encrypted`].
+    pub fn import_encrypted(
+        keystore: &EncryptedSpendingKey,
+        password: &str,
+    ) -> Result<Self, IronfishError> {
+        keystore.decrypt(password)
+    }
+
     /// Retrieve the private spending key
     pub fn spending_key(&self) -> [u8; 32] {
         self.spending_key
@@ -225,6 +271,12 @@ impl SaplingKey {
         bytes_to_hex(&self.spending_key)
     }
 
+    /// Private spending key in Bech32m form for the given network, which has
+    /// a checksum that catches typos.
+    pub fn bech32_spending_key(&self, network: Network) -> String {
+        bech32m::encode(network.spending_key_prefix(), &self.spending_key)
+    }
+
     /// Private spending key as words. This is even more human readable.
     ///
     /// We abuse the bip-39 to directly encode the key as words, instead of as
@@ -294,7 +346,7 @@ impl SaplingKey {
 
     /// Adapter to convert this key to a proof generation key for use in
     /// sapling functions
-    pub(crate) fn sapling_proof_generation_key(&self) -> ProofGenerationKey {
+    pub fn sapling_proof_generation_key(&self) -> ProofGenerationKey {
         ProofGenerationKey {
             ak: self.authorizing_key,
             nsk: self.proof_authorizing_key,
@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
+    bech32m::{self, Network},
     errors::IronfishError,
     serializing::{bytes_to_hex, hex_to_bytes, point_to_bytes},
 };
@@ -10,7 +11,7 @@ use ff::Field;
 use group::GroupEncoding;
 use ironfish_zkp::{Diversifier, PaymentAddress};
 use jubjub::SubgroupPoint;
-use rand::thread_rng;
+use rand::{thread_rng, CryptoRng, RngCore};
 
 use std::{convert::TryInto, io};
 
@@ -108,6 +109,16 @@ impl PublicAddress {
         }
     }
 
+    /// Load a public address from its Bech32m form for the given network.
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes = bech32m::decode_with_prefix(value, network.public_address_prefix())?;
+        let address_bytes: [u8; 43] = bytes
+            .try_into()
+            .map_err(|_| IronfishError::InvalidPublicAddress)?;
+
+        Self::new(&address_bytes)
+    }
+
     /// Retrieve the public address in byte form. It is comprised of the
     /// 11 byte diversifier followed by the 32 byte transmission key.
     pub fn public_address(&self) -> [u8; 43] {
@@ -125,6 +136,12 @@ impl PublicAddress {
         bytes_to_hex(&self.public_address())
     }
 
+    /// Retrieve the public address in Bech32m form for the given network.
+    /// Unlike hex, this has a checksum that catches typos.
+    pub fn bech32_public_address(&self, network: Network) -> String {
+        bech32m::encode(network.public_address_prefix(), &self.public_address())
+    }
+
     /// Store the bytes of this public address in the given writer.
     pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
         writer.write_all(&self.public_address())?;
@@ -168,7 +185,16 @@ impl PublicAddress {
     ///  *  the ephemeral secret key as a scalar FS
     ///  *  the ephemeral public key as an edwards point
     pub fn generate_diffie_hellman_keys(&self) -> (jubjub::Fr, SubgroupPoint) {
-        let secret_key: jubjub::Fr = jubjub::Fr::random(thread_rng());
+        self.generate_diffie_hellman_keys_with_rng(&mut thread_rng())
+    }
+
+    /// Same as [`PublicAddress::generate_diffie_hellman_keys`], but draws the
+    /// ephemeral secret key from the given rng.
+    pub fn generate_diffie_hellman_keys_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> (jubjub::Fr, SubgroupPoint) {
+        let secret_key: jubjub::Fr = jubjub::Fr::random(rng);
         let public_key = self.diversifier_point * secret_key;
 
         (secret_key, public_key)
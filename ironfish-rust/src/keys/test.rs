@@ -2,9 +2,18 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use super::{shared_secret, PublicAddress, SaplingKey};
+use super::{
+    shared_secret, EncryptedSpendingKey, ExtendedSpendingKey, IncomingViewKey, KdfParams,
+    OutgoingViewKey, PublicAddress, SaplingKey, ENCRYPTED_SPENDING_KEY_LENGTH,
+    EXTENDED_SPENDING_KEY_LENGTH,
+};
+use crate::{
+    bech32m::Network,
+    errors::{Bech32Error, IronfishError},
+};
 use group::Curve;
 use jubjub::ExtendedPoint;
+use rand::thread_rng;
 
 #[test]
 fn test_key_generation_and_construction() {
@@ -88,3 +97,366 @@ fn test_hex_conversion() {
 
     assert!(PublicAddress::from_hex("invalid").is_err());
 }
+
+#[test]
+fn test_extended_key_derivation() {
+    let seed = [7u8; 32];
+    let master = ExtendedSpendingKey::master(&seed).expect("should derive master key");
+    assert_eq!(master.depth(), 0);
+    assert_eq!(master.parent_fingerprint(), [0; 4]);
+    assert_eq!(master.child_index(), 0);
+
+    // Derivation is deterministic
+    let master_again = ExtendedSpendingKey::master(&seed).expect("should derive master key");
+    assert_eq!(
+        master.sapling_key().spending_key(),
+        master_again.sapling_key().spending_key()
+    );
+
+    let child = master.derive_child(0).expect("should derive child key");
+    assert_eq!(child.depth(), 1);
+    assert_eq!(child.child_index(), 0);
+    assert_eq!(child.parent_fingerprint(), master.fingerprint()[..4]);
+    assert_ne!(
+        child.sapling_key().spending_key(),
+        master.sapling_key().spending_key()
+    );
+
+    let sibling = master.derive_child(1).expect("should derive child key");
+    assert_ne!(
+        child.sapling_key().spending_key(),
+        sibling.sapling_key().spending_key()
+    );
+
+    let grandchild = child.derive_child(5).expect("should derive grandchild key");
+    let from_path = master
+        .derive_path(&[0, 5])
+        .expect("should derive key from path");
+    assert_eq!(from_path.depth(), 2);
+    assert_eq!(
+        grandchild.sapling_key().spending_key(),
+        from_path.sapling_key().spending_key()
+    );
+
+    // Different seeds give unrelated keys
+    let other_master = ExtendedSpendingKey::master(&[8u8; 32]).expect("should derive master key");
+    assert_ne!(
+        other_master
+            .derive_child(0)
+            .expect("should derive child key")
+            .sapling_key()
+            .spending_key(),
+        child.sapling_key().spending_key()
+    );
+
+    assert!(matches!(
+        ExtendedSpendingKey::master(&[7u8; 31]),
+        Err(IronfishError::InvalidSeed)
+    ));
+    assert!(matches!(
+        ExtendedSpendingKey::master(&[7u8; 253]),
+        Err(IronfishError::InvalidSeed)
+    ));
+}
+
+#[test]
+fn test_extended_key_serialization() {
+    let key = ExtendedSpendingKey::master(&[3u8; 64])
+        .and_then(|master| master.derive_path(&[44, 1]))
+        .expect("should derive key");
+
+    let mut serialized_key = vec![];
+    key.write(&mut serialized_key)
+        .expect("should be able to serialize key");
+    assert_eq!(serialized_key.len(), EXTENDED_SPENDING_KEY_LENGTH);
+
+    let read_back_key =
+        ExtendedSpendingKey::read(&serialized_key[..]).expect("should be able to load key");
+    assert_eq!(read_back_key.depth(), key.depth());
+    assert_eq!(read_back_key.child_index(), key.child_index());
+    assert_eq!(read_back_key.parent_fingerprint(), key.parent_fingerprint());
+    assert_eq!(read_back_key.fingerprint(), key.fingerprint());
+
+    // The chain code is carried along, so children match too
+    assert_eq!(
+        read_back_key
+            .derive_child(2)
+            .expect("should derive child key")
+            .sapling_key()
+            .spending_key(),
+        key.derive_child(2)
+            .expect("should derive child key")
+            .sapling_key()
+            .spending_key()
+    );
+
+    let hex_key = key.hex_key();
+    let from_hex = ExtendedSpendingKey::from_hex(&hex_key).expect("should load key from hex");
+    assert_eq!(from_hex.hex_key(), hex_key);
+    assert!(ExtendedSpendingKey::from_hex(&hex_key[2..]).is_err());
+}
+
+#[test]
+fn test_diversified_addresses() {
+    let key = SaplingKey::generate_key();
+    let view_key = key.incoming_view_key();
+
+    // The default address is the same every time
+    let default_address = key.generate_public_address();
+    assert_eq!(default_address, key.generate_public_address());
+
+    let (first_index, first_address) = key
+        .next_valid_address(0)
+        .expect("should find a valid address");
+    assert_eq!(first_address, default_address);
+    assert_eq!(
+        key.address_at(first_index)
+            .expect("address at a valid index should succeed"),
+        first_address
+    );
+
+    let (second_index, second_address) = key
+        .next_valid_address(first_index + 1)
+        .expect("should find a valid address");
+    assert!(second_index > first_index);
+    assert_ne!(second_address, first_address);
+
+    // Indices in between have no valid address
+    for index in first_index + 1..second_index {
+        assert!(key.address_at(index).is_err());
+    }
+
+    // A key restored from the same spending key gives the same addresses
+    let restored_key = SaplingKey::new(key.spending_key()).expect("should restore key");
+    assert_eq!(
+        restored_key
+            .address_at(second_index)
+            .expect("address at a valid index should succeed"),
+        second_address
+    );
+
+    // Diversifiers map back to the index they were derived at
+    assert_eq!(
+        view_key.diversifier_index(&first_address.diversifier.0),
+        Some(first_index)
+    );
+    assert_eq!(
+        view_key.diversifier_index(&second_address.diversifier.0),
+        Some(second_index)
+    );
+    let (last_index, last_address) = key
+        .next_valid_address(u64::MAX - 100)
+        .expect("should find a valid address");
+    assert_eq!(
+        view_key.diversifier_index(&last_address.diversifier.0),
+        Some(last_index)
+    );
+
+    // The search stops at the last index instead of wrapping around
+    match key.next_valid_address(u64::MAX) {
+        Ok((index, address)) => {
+            assert_eq!(index, u64::MAX);
+            assert_eq!(key.address_at(u64::MAX).ok(), Some(address));
+        }
+        Err(e) => {
+            assert!(matches!(e, IronfishError::InvalidDiversificationPoint));
+            assert!(key.address_at(u64::MAX).is_err());
+        }
+    }
+
+    // Another key derives different addresses at the same index
+    let other_key = SaplingKey::generate_key();
+    let (other_index, other_address) = other_key
+        .next_valid_address(second_index)
+        .expect("should find a valid address");
+    assert_ne!(key.address_at(other_index).ok(), Some(other_address));
+}
+
+#[test]
+fn test_bech32_encoding() {
+    let key = SaplingKey::generate_key();
+    let address = key.generate_public_address();
+
+    let encoded_address = address.bech32_public_address(Network::Mainnet);
+    assert!(encoded_address.starts_with("ifaddr1"));
+    assert_eq!(
+        PublicAddress::from_bech32(&encoded_address, Network::Mainnet)
+            .expect("should decode address"),
+        address
+    );
+
+    // Hex keeps working alongside
+    assert_eq!(
+        PublicAddress::from_hex(&address.hex_public_address()).expect("should decode address"),
+        address
+    );
+
+    // Addresses for another network are rejected
+    let testnet_address = address.bech32_public_address(Network::Testnet);
+    assert!(testnet_address.starts_with("tifaddr1"));
+    assert!(matches!(
+        PublicAddress::from_bech32(&testnet_address, Network::Mainnet),
+        Err(IronfishError::InvalidBech32(Bech32Error::WrongPrefix))
+    ));
+
+    // A typo is reported along with its position
+    let typo_position = encoded_address.len() - 10;
+    let mut mistyped_address = encoded_address.clone().into_bytes();
+    mistyped_address[typo_position] = if mistyped_address[typo_position] == b'q' {
+        b'p'
+    } else {
+        b'q'
+    };
+    let mistyped_address = String::from_utf8(mistyped_address).unwrap();
+    match PublicAddress::from_bech32(&mistyped_address, Network::Mainnet) {
+        Err(IronfishError::InvalidBech32(Bech32Error::InvalidChecksum(position))) => {
+            assert_eq!(position, Some(typo_position))
+        }
+        _ => panic!("expected a checksum error"),
+    }
+
+    let incoming_view_key = key.incoming_view_key();
+    let decoded_incoming_view_key = IncomingViewKey::from_bech32(
+        &incoming_view_key.bech32_key(Network::Mainnet),
+        Network::Mainnet,
+    )
+    .expect("should decode incoming view key");
+    assert_eq!(
+        decoded_incoming_view_key.hex_key(),
+        incoming_view_key.hex_key()
+    );
+
+    let outgoing_view_key = key.outgoing_view_key();
+    let decoded_outgoing_view_key = OutgoingViewKey::from_bech32(
+        &outgoing_view_key.bech32_key(Network::Testnet),
+        Network::Testnet,
+    )
+    .expect("should decode outgoing view key");
+    assert_eq!(
+        decoded_outgoing_view_key.hex_key(),
+        outgoing_view_key.hex_key()
+    );
+
+    let decoded_key =
+        SaplingKey::from_bech32(&key.bech32_spending_key(Network::Mainnet), Network::Mainnet)
+            .expect("should decode spending key");
+    assert_eq!(decoded_key.spending_key(), key.spending_key());
+
+    // Keys can't be decoded as another kind of key
+    assert!(matches!(
+        IncomingViewKey::from_bech32(&key.bech32_spending_key(Network::Mainnet), Network::Mainnet),
+        Err(IronfishError::InvalidBech32(Bech32Error::WrongPrefix))
+    ));
+}
+
+#[test]
+fn test_encrypted_key_round_trip() {
+    let key = SaplingKey::generate_key();
+
+    let keystore = key
+        .export_encrypted("correct horse battery staple")
+        .expect("should encrypt key");
+    assert_eq!(keystore.kdf_params(), KdfParams::default());
+
+    let hex_keystore = keystore.hex_keystore();
+    assert_eq!(hex_keystore.len(), ENCRYPTED_SPENDING_KEY_LENGTH * 2);
+    assert!(!hex_keystore.contains(&key.hex_spending_key()));
+
+    let deserialized_keystore =
+        EncryptedSpendingKey::from_hex(&hex_keystore).expect("should deserialize keystore");
+    assert_eq!(deserialized_keystore, keystore);
+    assert_eq!(
+        deserialized_keystore.address_fingerprint(),
+        EncryptedSpendingKey::fingerprint_address(&key.generate_public_address())
+    );
+
+    let imported_key =
+        SaplingKey::import_encrypted(&deserialized_keystore, "correct horse battery staple")
+            .expect("should decrypt key");
+    assert_eq!(imported_key.spending_key(), key.spending_key());
+
+    assert!(matches!(
+        SaplingKey::import_encrypted(&deserialized_keystore, "wrong password"),
+        Err(IronfishError::InvalidDecryptionKey)
+    ));
+}
+
+#[test]
+fn test_encrypted_key_tampering() {
+    let key = SaplingKey::generate_key();
+    let kdf_params = KdfParams {
+        log_n: 10,
+        r: 8,
+        p: 1,
+    };
+
+    let keystore =
+        EncryptedSpendingKey::encrypt_with_rng(&key, "password", kdf_params, &mut thread_rng())
+            .expect("should encrypt key");
+    let mut bytes = vec![];
+    keystore
+        .write(&mut bytes)
+        .expect("should serialize keystore");
+
+    // Salting makes every export different
+    let other_keystore =
+        EncryptedSpendingKey::encrypt_with_rng(&key, "password", kdf_params, &mut thread_rng())
+            .expect("should encrypt key");
+    assert_ne!(other_keystore, keystore);
+
+    // Weakening the KDF invalidates the MAC
+    let mut weakened = bytes.clone();
+    weakened[1] = 9;
+    let weakened = EncryptedSpendingKey::read(&weakened[..]).expect("should deserialize keystore");
+    assert!(matches!(
+        weakened.decrypt("password"),
+        Err(IronfishError::InvalidDecryptionKey)
+    ));
+
+    // So does swapping in the fingerprint of another account
+    let mut relabeled = bytes.clone();
+    relabeled[50..82].copy_from_slice(&EncryptedSpendingKey::fingerprint_address(
+        &SaplingKey::generate_key().generate_public_address(),
+    ));
+    let relabeled =
+        EncryptedSpendingKey::read(&relabeled[..]).expect("should deserialize keystore");
+    assert!(matches!(
+        relabeled.decrypt("password"),
+        Err(IronfishError::InvalidDecryptionKey)
+    ));
+
+    // Unreasonable KDF parameters are rejected before running the KDF
+    let mut expensive = bytes.clone();
+    expensive[1] = 30;
+    assert!(matches!(
+        EncryptedSpendingKey::read(&expensive[..]),
+        Err(IronfishError::InvalidData)
+    ));
+
+    // As are parameters scrypt itself would reject, which would otherwise
+    // panic when deriving the key
+    let mut small_block_size = bytes.clone();
+    small_block_size[1] = 16;
+    small_block_size[2..6].copy_from_slice(&1u32.to_le_bytes());
+    assert!(matches!(
+        EncryptedSpendingKey::read(&small_block_size[..]),
+        Err(IronfishError::InvalidData)
+    ));
+
+    let invalid_params = KdfParams {
+        log_n: 16,
+        r: 1,
+        p: 1,
+    };
+    assert!(matches!(
+        EncryptedSpendingKey::encrypt_with_rng(&key, "password", invalid_params, &mut thread_rng()),
+        Err(IronfishError::InvalidData)
+    ));
+
+    let mut unknown_version = bytes;
+    unknown_version[0] = 2;
+    assert!(matches!(
+        EncryptedSpendingKey::read(&unknown_version[..]),
+        Err(IronfishError::UnsupportedVersion)
+    ));
+}
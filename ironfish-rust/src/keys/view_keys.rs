@@ -14,17 +14,30 @@
 
 use super::PublicAddress;
 use crate::{
+    bech32m::{self, Network},
     errors::IronfishError,
     serializing::{bytes_to_hex, hex_to_bytes, point_to_bytes, read_scalar, scalar_to_bytes},
 };
 use bip39::{Language, Mnemonic};
 use blake2b_simd::Params as Blake2b;
 use jubjub::SubgroupPoint;
-use rand::{thread_rng, Rng};
 
 use std::io;
 
 const DIFFIE_HELLMAN_PERSONALIZATION: &[u8; 16] = b"Beanstalk shared";
+const DIVERSIFIER_KEY_PERSONALIZATION: &[u8; 16] = b"Ironfish Dvrsfky";
+const DIVERSIFIER_PRP_PERSONALIZATION: &[u8; 16] = b"Ironfish Dvrsprp";
+
+/// Diversifiers are 88 bits, which the permutation splits into two halves.
+const DIVERSIFIER_HALF_BITS: u32 = 44;
+const DIVERSIFIER_HALF_MASK: u64 = (1 << DIVERSIFIER_HALF_BITS) - 1;
+
+/// Number of Feistel rounds in the diversifier permutation, same as FF1.
+const DIVERSIFIER_PRP_ROUNDS: u8 = 10;
+
+/// Most indices [`IncomingViewKey::next_valid_address`] tries before giving
+/// up. The chance of this many invalid diversifiers in a row is 2^-256.
+pub const MAX_DIVERSIFIER_SEARCH: u64 = 256;
 
 /// Key that allows someone to view a transaction that you have received.
 ///
@@ -69,11 +82,30 @@ impl IncomingViewKey {
         Self::read(&mut byte_arr[..].as_ref())
     }
 
+    /// Load a key from its Bech32m form for the given network.
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes = bech32m::decode_with_prefix(value, network.incoming_view_key_prefix())?;
+        if bytes.len() != 32 {
+            return Err(IronfishError::InvalidViewingKey);
+        }
+
+        Self::read(&mut bytes[..].as_ref())
+    }
+
     /// Viewing key as hexadecimal, for readability.
     pub fn hex_key(&self) -> String {
         bytes_to_hex(&scalar_to_bytes(&self.view_key))
     }
 
+    /// Viewing key in Bech32m form for the given network, which has a
+    /// checksum that catches typos.
+    pub fn bech32_key(&self, network: Network) -> String {
+        bech32m::encode(
+            network.incoming_view_key_prefix(),
+            &scalar_to_bytes(&self.view_key),
+        )
+    }
+
     /// Even more readable
     pub fn words_key(&self, language_code: &str) -> Result<String, IronfishError> {
         let language = Language::from_language_code(language_code)
@@ -93,23 +125,115 @@ impl IncomingViewKey {
         PublicAddress::from_view_key(self, diversifier)
     }
 
-    /// Generate a public address from this key,
-    /// picking a diversifier that is guaranteed to work with it.
+    /// Generate the default public address of this key, which is the first
+    /// valid address returned by [`IncomingViewKey::next_valid_address`].
     ///
-    /// This method always succeeds, retrying with a different diversifier if
-    /// one doesn't work.
+    /// This method always succeeds, and always returns the same address for
+    /// the same key.
     pub fn generate_public_address(&self) -> PublicAddress {
-        let public_address;
-        loop {
-            let mut diversifier_candidate = [0u8; 11];
-            thread_rng().fill(&mut diversifier_candidate);
-
-            if let Ok(key) = self.public_address(&diversifier_candidate) {
-                public_address = key;
-                break;
+        self.next_valid_address(0)
+            .expect("about half of all diversifiers are valid")
+            .1
+    }
+
+    /// Generate the public address with the diversifier at `index`.
+    ///
+    /// Diversifiers are derived from the index with a keyed permutation, so
+    /// the same key always gives the same address at an index, and addresses
+    /// at different indices can't be linked without the key.
+    ///
+    /// This fails for about half of all indices, as not all diversifiers are
+    /// valid. Use [`IncomingViewKey::next_valid_address`] to skip those.
+    pub fn address_at(&self, index: u64) -> Result<PublicAddress, IronfishError> {
+        self.public_address(&self.diversifier_at(index))
+    }
+
+    /// Find the first index at or after `from_index` with a valid address.
+    /// Returns the index along with the address, so a wallet can record where
+    /// to continue from.
+    ///
+    /// Gives up after [`MAX_DIVERSIFIER_SEARCH`] indices, or at the last
+    /// index, with [`IronfishError::InvalidDiversificationPoint`]. About half
+    /// of all indices are valid, so that only happens near the end of the
+    /// index space.
+    pub fn next_valid_address(
+        &self,
+        from_index: u64,
+    ) -> Result<(u64, PublicAddress), IronfishError> {
+        let last_index = from_index.saturating_add(MAX_DIVERSIFIER_SEARCH - 1);
+        for index in from_index..=last_index {
+            if let Ok(address) = self.address_at(index) {
+                return Ok((index, address));
+            }
+        }
+
+        Err(IronfishError::InvalidDiversificationPoint)
+    }
+
+    /// The index the given diversifier was derived at with this key, if any.
+    ///
+    /// Every diversifier maps back to some value, so this can't tell by
+    /// itself if an address belongs to this key. Compare the address at the
+    /// returned index with the expected one for that.
+    pub fn diversifier_index(&self, diversifier: &[u8; 11]) -> Option<u64> {
+        let mut bytes = [0; 16];
+        bytes[..11].copy_from_slice(diversifier);
+        let index = self.permute_diversifier(u128::from_le_bytes(bytes), false);
+
+        u64::try_from(index).ok()
+    }
+
+    /// Derive the diversifier at `index` with this key.
+    fn diversifier_at(&self, index: u64) -> [u8; 11] {
+        let permuted = self.permute_diversifier(index as u128, true);
+
+        let mut diversifier = [0; 11];
+        diversifier.copy_from_slice(&permuted.to_le_bytes()[..11]);
+        diversifier
+    }
+
+    /// A Feistel network keyed by this view key, which permutes the 88-bit
+    /// diversifier space. `forward` maps indices to diversifiers, and the
+    /// rounds run in reverse to map diversifiers back to indices.
+    fn permute_diversifier(&self, value: u128, forward: bool) -> u128 {
+        let diversifier_key = Blake2b::new()
+            .hash_length(32)
+            .personal(DIVERSIFIER_KEY_PERSONALIZATION)
+            .hash(&scalar_to_bytes(&self.view_key));
+
+        let round_function = |round: u8, half: u64| {
+            let hash = Blake2b::new()
+                .hash_length(8)
+                .key(diversifier_key.as_bytes())
+                .personal(DIVERSIFIER_PRP_PERSONALIZATION)
+                .to_state()
+                .update(&[round])
+                .update(&half.to_le_bytes())
+                .finalize();
+
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(hash.as_bytes());
+            u64::from_le_bytes(bytes) & DIVERSIFIER_HALF_MASK
+        };
+
+        let mut left = (value >> DIVERSIFIER_HALF_BITS) as u64 & DIVERSIFIER_HALF_MASK;
+        let mut right = value as u64 & DIVERSIFIER_HALF_MASK;
+
+        if forward {
+            for round in 0..DIVERSIFIER_PRP_ROUNDS {
+                let next_right = left ^ round_function(round, right);
+                left = right;
+                right = next_right;
+            }
+        } else {
+            for round in (0..DIVERSIFIER_PRP_ROUNDS).rev() {
+                let previous_left = right ^ round_function(round, left);
+                right = left;
+                left = previous_left;
             }
         }
-        public_address
+
+        ((left as u128) << DIVERSIFIER_HALF_BITS) | right as u128
     }
 
     /// Calculate the shared secret key given the ephemeral public key that was
@@ -158,11 +282,27 @@ impl OutgoingViewKey {
         Ok(Self { view_key })
     }
 
+    /// Load a key from its Bech32m form for the given network.
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes = bech32m::decode_with_prefix(value, network.outgoing_view_key_prefix())?;
+        let view_key = bytes
+            .try_into()
+            .map_err(|_| IronfishError::InvalidViewingKey)?;
+
+        Ok(Self { view_key })
+    }
+
     /// Viewing key as hexadecimal, for readability.
     pub fn hex_key(&self) -> String {
         bytes_to_hex(&self.view_key)
     }
 
+    /// Viewing key in Bech32m form for the given network, which has a
+    /// checksum that catches typos.
+    pub fn bech32_key(&self, network: Network) -> String {
+        bech32m::encode(network.outgoing_view_key_prefix(), &self.view_key)
+    }
+
     /// Even more readable
     pub fn words_key(&self, language_code: &str) -> Result<String, IronfishError> {
         let language = Language::from_language_code(language_code)
@@ -1,12 +1,21 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+use crate::errors::IronfishError;
 use bellman::groth16;
 use bls12_381::Bls12;
+use params::{
+    HashReader, MINT_PARAMS_HASH, MINT_VK_HASH, OUTPUT_PARAMS_HASH, OUTPUT_VK_HASH,
+    SPEND_PARAMS_HASH, SPEND_VK_HASH,
+};
+use std::{fs::File, io, path::Path};
 
+mod params;
 mod serializing;
 
 pub mod assets;
+pub mod bech32m;
+pub mod chain_context;
 pub mod errors;
 pub mod keys;
 pub mod merkle_note;
@@ -14,6 +23,7 @@ pub mod merkle_note_hash;
 pub mod mining;
 pub mod nacl;
 pub mod note;
+pub mod payment_uri;
 pub mod rolling_filter;
 pub mod sapling_bls12;
 pub mod transaction;
@@ -25,7 +35,8 @@ pub use {
     merkle_note_hash::MerkleNoteHash,
     note::Note,
     transaction::{
-        outputs::OutputDescription, spending::SpendDescription, ProposedTransaction, Transaction,
+        mints::MintDescription, outputs::OutputDescription, spending::SpendDescription,
+        ProposedTransaction, Transaction, UnsignedTransaction,
     },
 };
 
@@ -35,47 +46,206 @@ pub(crate) mod test_util; // I'm not sure if this is the right way to publish th
 // The main entry-point to the sapling API. Construct this with loaded parameters, and then call
 // methods on it to do the actual work.
 //
-// spend and output are two arithmetic circuits for use in zksnark calculations provided by Bellman.
+// spend, output, and mint are arithmetic circuits for use in zksnark calculations provided by Bellman.
 // Though the *_params have a verifying key on them, they are not the prepared verifying keys,
 // so we store the prepared keys separately at the time of loading the params.
 //
-// The values are all loaded from a file in serialized form.
+// The proving parameters are optional. A Sapling instance created in verify-only mode holds
+// just the verifying keys, which are a tiny prefix of each parameter file, and can verify
+// transactions but not create proofs.
+//
+// The values are all loaded from a file in serialized form, and each file read at runtime is
+// checked against a pinned BLAKE2b digest before it is used.
 pub struct Sapling {
-    spend_params: groth16::Parameters<Bls12>,
-    output_params: groth16::Parameters<Bls12>,
+    spend_params: Option<groth16::Parameters<Bls12>>,
+    output_params: Option<groth16::Parameters<Bls12>>,
+    mint_params: Option<groth16::Parameters<Bls12>>,
+    spend_vk: groth16::VerifyingKey<Bls12>,
+    output_vk: groth16::VerifyingKey<Bls12>,
+    mint_vk: groth16::VerifyingKey<Bls12>,
     spend_verifying_key: groth16::PreparedVerifyingKey<Bls12>,
     output_verifying_key: groth16::PreparedVerifyingKey<Bls12>,
+    mint_verifying_key: groth16::PreparedVerifyingKey<Bls12>,
 }
 
 impl Sapling {
-    /// Initialize a Sapling instance and prepare for proving. Load the parameters from a config file
-    /// at a known location (`./sapling_params`, for now).
-    pub fn load() -> Self {
-        // TODO: We'll need to build our own parameters using a trusted set up at some point.
-        // These params were borrowed from zcash
+    /// Initialize a Sapling instance and prepare for proving, using the parameters embedded in
+    /// the binary at compile time.
+    ///
+    /// The embedded parameters are as trusted as the binary itself, so they aren't checked
+    /// against the pinned digests, which guard the files read at runtime.
+    ///
+    /// Returns [`IronfishError::InvalidParameters`] if the embedded parameters are malformed.
+    #[cfg(feature = "embedded-params")]
+    pub fn load() -> Result<Self, IronfishError> {
+        // TODO: Replace these with parameters from a multi-party trusted setup, which can be run
+        // with the ceremony subcommands of ironfish-zkp's generate_params.
         let spend_bytes = include_bytes!("sapling_params/sapling-spend.params");
         let output_bytes = include_bytes!("sapling_params/sapling-output.params");
+        let mint_bytes = include_bytes!("sapling_params/sapling-mint.params");
 
-        let spend_params = Sapling::load_params(&spend_bytes[..]);
-        let output_params = Sapling::load_params(&output_bytes[..]);
+        let read_params = |bytes: &[u8]| {
+            groth16::Parameters::read(bytes, false).map_err(|_| IronfishError::InvalidParameters)
+        };
 
-        let spend_vk = groth16::prepare_verifying_key(&spend_params.vk);
-        let output_vk = groth16::prepare_verifying_key(&output_params.vk);
+        Ok(Sapling::from_params(
+            read_params(&spend_bytes[..])?,
+            read_params(&output_bytes[..])?,
+            read_params(&mint_bytes[..])?,
+        ))
+    }
 
-        Sapling {
-            spend_verifying_key: spend_vk,
-            output_verifying_key: output_vk,
+    /// Initialize a Sapling instance and prepare for proving, reading the parameters from the
+    /// given readers.
+    ///
+    /// The digest of each file is always checked. Passing `checked` additionally verifies that
+    /// every point in the parameters is on the curve and in the right subgroup, which is slow
+    /// but worth doing for parameters that were downloaded from an untrusted mirror.
+    pub fn from_readers<R: io::Read>(
+        spend_reader: R,
+        output_reader: R,
+        mint_reader: R,
+        checked: bool,
+    ) -> Result<Self, IronfishError> {
+        let spend_params = Sapling::load_params(spend_reader, SPEND_PARAMS_HASH, checked)?;
+        let output_params = Sapling::load_params(output_reader, OUTPUT_PARAMS_HASH, checked)?;
+        let mint_params = Sapling::load_params(mint_reader, MINT_PARAMS_HASH, checked)?;
+
+        Ok(Sapling::from_params(
             spend_params,
             output_params,
+            mint_params,
+        ))
+    }
+
+    /// Initialize a Sapling instance and prepare for proving, reading the parameters from the
+    /// files at the given paths. See [`Sapling::from_readers`] for the meaning of `checked`.
+    pub fn from_paths<P: AsRef<Path>>(
+        spend_path: P,
+        output_path: P,
+        mint_path: P,
+        checked: bool,
+    ) -> Result<Self, IronfishError> {
+        Sapling::from_readers(
+            io::BufReader::new(File::open(spend_path)?),
+            io::BufReader::new(File::open(output_path)?),
+            io::BufReader::new(File::open(mint_path)?),
+            checked,
+        )
+    }
+
+    /// Initialize a Sapling instance that can only verify proofs. Only the verifying keys at
+    /// the start of each parameter file are read from the given readers, and each is checked
+    /// against the digest pinned for the verifying key alone.
+    pub fn verifier_from_readers<R: io::Read>(
+        spend_reader: R,
+        output_reader: R,
+        mint_reader: R,
+    ) -> Result<Self, IronfishError> {
+        let spend_vk = Sapling::load_verifying_key(spend_reader, SPEND_VK_HASH)?;
+        let output_vk = Sapling::load_verifying_key(output_reader, OUTPUT_VK_HASH)?;
+        let mint_vk = Sapling::load_verifying_key(mint_reader, MINT_VK_HASH)?;
+
+        Ok(Sapling {
+            spend_verifying_key: groth16::prepare_verifying_key(&spend_vk),
+            output_verifying_key: groth16::prepare_verifying_key(&output_vk),
+            mint_verifying_key: groth16::prepare_verifying_key(&mint_vk),
+            spend_vk,
+            output_vk,
+            mint_vk,
+            spend_params: None,
+            output_params: None,
+            mint_params: None,
+        })
+    }
+
+    /// Initialize a Sapling instance that can only verify proofs, reading the verifying keys
+    /// from the parameter files at the given paths.
+    pub fn verifier_from_paths<P: AsRef<Path>>(
+        spend_path: P,
+        output_path: P,
+        mint_path: P,
+    ) -> Result<Self, IronfishError> {
+        Sapling::verifier_from_readers(
+            io::BufReader::new(File::open(spend_path)?),
+            io::BufReader::new(File::open(output_path)?),
+            io::BufReader::new(File::open(mint_path)?),
+        )
+    }
+
+    fn from_params(
+        spend_params: groth16::Parameters<Bls12>,
+        output_params: groth16::Parameters<Bls12>,
+        mint_params: groth16::Parameters<Bls12>,
+    ) -> Self {
+        Sapling {
+            spend_verifying_key: groth16::prepare_verifying_key(&spend_params.vk),
+            output_verifying_key: groth16::prepare_verifying_key(&output_params.vk),
+            mint_verifying_key: groth16::prepare_verifying_key(&mint_params.vk),
+            spend_vk: spend_params.vk.clone(),
+            output_vk: output_params.vk.clone(),
+            mint_vk: mint_params.vk.clone(),
+            spend_params: Some(spend_params),
+            output_params: Some(output_params),
+            mint_params: Some(mint_params),
         }
     }
 
-    /// Load sapling parameters from a provided filename. The parameters are huge and take a
+    /// Whether this instance holds the proving parameters, or only the verifying keys.
+    pub fn can_prove(&self) -> bool {
+        self.spend_params.is_some() && self.output_params.is_some() && self.mint_params.is_some()
+    }
+
+    pub(crate) fn spend_params(&self) -> Result<&groth16::Parameters<Bls12>, IronfishError> {
+        self.spend_params
+            .as_ref()
+            .ok_or(IronfishError::MissingProvingParameters)
+    }
+
+    pub(crate) fn output_params(&self) -> Result<&groth16::Parameters<Bls12>, IronfishError> {
+        self.output_params
+            .as_ref()
+            .ok_or(IronfishError::MissingProvingParameters)
+    }
+
+    pub(crate) fn mint_params(&self) -> Result<&groth16::Parameters<Bls12>, IronfishError> {
+        self.mint_params
+            .as_ref()
+            .ok_or(IronfishError::MissingProvingParameters)
+    }
+
+    /// Load sapling parameters from a provided reader. The parameters are huge and take a
     /// couple seconds to load. They primarily contain the "toxic waste" for a specific sapling
     /// curve.
     ///
+    /// Returns [`IronfishError::InvalidParameters`] if the parameters are malformed or don't
+    /// match `expected_hash`.
+    ///
     /// NOTE: If this is stupidly slow for you, try compiling in --release mode
-    fn load_params(bytes: &[u8]) -> groth16::Parameters<Bls12> {
-        groth16::Parameters::read(bytes, false).unwrap()
+    fn load_params<R: io::Read>(
+        reader: R,
+        expected_hash: &str,
+        checked: bool,
+    ) -> Result<groth16::Parameters<Bls12>, IronfishError> {
+        let mut reader = HashReader::new(reader);
+        let params = groth16::Parameters::read(&mut reader, checked)
+            .map_err(|_| IronfishError::InvalidParameters)?;
+        reader.finish(expected_hash)?;
+
+        Ok(params)
+    }
+
+    /// Load only the verifying key from the start of a sapling parameter file, checking its
+    /// digest against `expected_hash`. Nothing after the verifying key is read.
+    fn load_verifying_key<R: io::Read>(
+        reader: R,
+        expected_hash: &str,
+    ) -> Result<groth16::VerifyingKey<Bls12>, IronfishError> {
+        let mut reader = HashReader::new(reader);
+        let vk = groth16::VerifyingKey::read(&mut reader)
+            .map_err(|_| IronfishError::InvalidParameters)?;
+        reader.finish_read(expected_hash)?;
+
+        Ok(vk)
     }
 }
@@ -7,7 +7,7 @@ use crate::errors::IronfishError;
 /// Implement a merkle note to store all the values that need to go into a merkle tree.
 /// A tree containing these values can serve as a snapshot of the entire chain.
 use super::{
-    keys::{shared_secret, IncomingViewKey, OutgoingViewKey, PublicAddress, SaplingKey},
+    keys::{shared_secret, IncomingViewKey, OutgoingViewKey, PublicAddress},
     note::{Note, ENCRYPTED_NOTE_SIZE},
     serializing::{aead, read_scalar},
     witness::{WitnessNode, WitnessTrait},
@@ -26,6 +26,12 @@ use std::{convert::TryInto, io};
 pub const ENCRYPTED_SHARED_KEY_SIZE: usize = 64;
 
 pub const NOTE_ENCRYPTION_KEY_SIZE: usize = ENCRYPTED_SHARED_KEY_SIZE + aead::MAC_SIZE;
+
+/// Size of a serialized [`MerkleNote`]: the value commitment, note commitment,
+/// ephemeral public key, encrypted note and note encryption keys.
+pub const MERKLE_NOTE_SIZE: usize =
+    32 + 32 + 32 + ENCRYPTED_NOTE_SIZE + aead::MAC_SIZE + NOTE_ENCRYPTION_KEY_SIZE;
+
 /// The note encryption keys are used to allow the spender to
 /// read notes that they have themselves have spent.
 /// In the case of miner notes, the note is created out of thin air
@@ -72,7 +78,7 @@ impl PartialEq for MerkleNote {
 
 impl MerkleNote {
     pub fn new(
-        spender_key: &SaplingKey,
+        outgoing_view_key: &OutgoingViewKey,
         note: &Note,
         value_commitment: &ValueCommitment,
         diffie_hellman_keys: &(jubjub::Fr, SubgroupPoint),
@@ -84,7 +90,7 @@ impl MerkleNote {
         key_bytes[32..].clone_from_slice(secret_key.to_repr().as_ref());
 
         let encryption_key = calculate_key_for_encryption_keys(
-            spender_key.outgoing_view_key(),
+            outgoing_view_key,
             &value_commitment.commitment().into(),
             &note.commitment_point(),
             public_key,
@@ -311,8 +317,12 @@ mod test {
             randomness: jubjub::Fr::random(thread_rng()),
         };
 
-        let merkle_note =
-            MerkleNote::new(&spender_key, &note, &value_commitment, &diffie_hellman_keys);
+        let merkle_note = MerkleNote::new(
+            spender_key.outgoing_view_key(),
+            &note,
+            &value_commitment,
+            &diffie_hellman_keys,
+        );
 
         assert_ne!(
             &merkle_note.note_encryption_keys,
@@ -354,8 +364,12 @@ mod test {
             randomness: jubjub::Fr::random(thread_rng()),
         };
 
-        let merkle_note =
-            MerkleNote::new(&spender_key, &note, &value_commitment, &diffie_hellman_keys);
+        let merkle_note = MerkleNote::new(
+            spender_key.outgoing_view_key(),
+            &note,
+            &value_commitment,
+            &diffie_hellman_keys,
+        );
         merkle_note
             .decrypt_note_for_owner(receiver_key.incoming_view_key())
             .expect("should be able to decrypt note");
@@ -375,8 +389,12 @@ mod test {
             randomness: jubjub::Fr::random(thread_rng()),
         };
 
-        let mut merkle_note =
-            MerkleNote::new(&spender_key, &note, &value_commitment, &diffie_hellman_keys);
+        let mut merkle_note = MerkleNote::new(
+            spender_key.outgoing_view_key(),
+            &note,
+            &value_commitment,
+            &diffie_hellman_keys,
+        );
         merkle_note
             .decrypt_note_for_owner(spender_key.incoming_view_key())
             .expect("should be able to decrypt note");
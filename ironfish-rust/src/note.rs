@@ -13,7 +13,7 @@ use byteorder::{ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
 use ff::{Field, PrimeField};
 use ironfish_zkp::{Nullifier, Rseed, SaplingNote};
 use jubjub::SubgroupPoint;
-use rand::thread_rng;
+use rand::{thread_rng, CryptoRng, RngCore};
 
 use std::{fmt, io, io::Read};
 
@@ -77,7 +77,18 @@ pub struct Note {
 impl<'a> Note {
     /// Construct a new Note.
     pub fn new(owner: PublicAddress, value: u64, memo: impl Into<Memo>) -> Self {
-        let randomness: jubjub::Fr = jubjub::Fr::random(thread_rng());
+        Self::new_with_rng(owner, value, memo, &mut thread_rng())
+    }
+
+    /// Same as [`Note::new`], but draws the note randomness from the given
+    /// rng, so the same seed always produces the same note.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        owner: PublicAddress,
+        value: u64,
+        memo: impl Into<Memo>,
+        rng: &mut R,
+    ) -> Self {
+        let randomness: jubjub::Fr = jubjub::Fr::random(rng);
 
         Self {
             owner,
@@ -269,7 +280,7 @@ impl<'a> Note {
     /// This is somewhat suboptimal with extra calculations and bytes being
     /// passed around. I'm not worried about it yet, since only notes actively
     /// being spent have to create these.
-    fn sapling_note(&self) -> SaplingNote {
+    pub(crate) fn sapling_note(&self) -> SaplingNote {
         SaplingNote {
             value: self.value,
             g_d: self.owner.diversifier.g_d().unwrap(),
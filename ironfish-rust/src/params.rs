@@ -0,0 +1,158 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Integrity checks for the Groth16 parameter files.
+//!
+//! Every parameter file is hashed with BLAKE2b-512 while it is being read, and
+//! the digest is compared against the one pinned below before the parameters
+//! are used. Verify-only instances never read past the verifying key at the
+//! start of each file, so the verifying keys have digests of their own.
+//!
+//! These have to be updated whenever the parameters are regenerated with
+//! `generate_params`, which prints both digests of each file it writes.
+
+use std::io;
+
+use blake2b_simd::{Params as Blake2b, State};
+
+use crate::{errors::IronfishError, serializing::bytes_to_hex};
+
+/// BLAKE2b-512 digest of `sapling-spend.params`
+// TODO: sapling-spend.params is not checked into this tree yet; pin its digest
+// alongside the file.
+pub(crate) const SPEND_PARAMS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000\
+                                            0000000000000000000000000000000000000000000000000000000000000000";
+
+/// BLAKE2b-512 digest of `sapling-output.params`
+pub(crate) const OUTPUT_PARAMS_HASH: &str = "483a56b96f5d33d8d9ee82f8c7b2c32bf99c2ea2e465d239eb7399cf68abb01a\
+                                             1ee179c7911bea56deb3ed8b737f56262a852572810a9611558711ce4fed0b37";
+
+/// BLAKE2b-512 digest of `sapling-mint.params`
+// TODO: sapling-mint.params is not checked into this tree yet; pin its digest
+// alongside the file.
+pub(crate) const MINT_PARAMS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000\
+                                           0000000000000000000000000000000000000000000000000000000000000000";
+
+/// BLAKE2b-512 digest of the verifying key in `sapling-spend.params`
+// TODO: pin alongside SPEND_PARAMS_HASH.
+pub(crate) const SPEND_VK_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000\
+                                        0000000000000000000000000000000000000000000000000000000000000000";
+
+/// BLAKE2b-512 digest of the verifying key in `sapling-output.params`
+pub(crate) const OUTPUT_VK_HASH: &str = "d8ec614e6d7a156cdef4c28b80444fb2e6094bdb6ad7aaf9e86ffc442f70e7b0\
+                                         c0d6fdd3b45b2d7cc66a3471c484955ce3384f9543190a84d4c45f23f6648518";
+
+/// BLAKE2b-512 digest of the verifying key in `sapling-mint.params`
+// TODO: pin alongside MINT_PARAMS_HASH.
+pub(crate) const MINT_VK_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000\
+                                       0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Wraps a reader and hashes every byte that is read through it.
+pub(crate) struct HashReader<R: io::Read> {
+    reader: R,
+    hasher: State,
+}
+
+impl<R: io::Read> HashReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        HashReader {
+            reader,
+            hasher: Blake2b::new().hash_length(64).to_state(),
+        }
+    }
+
+    /// Read the rest of the underlying reader, so the digest covers the whole
+    /// file even if only a prefix of it was needed, and check the digest
+    /// against `expected_hash`.
+    pub(crate) fn finish(mut self, expected_hash: &str) -> Result<(), IronfishError> {
+        io::copy(&mut self, &mut io::sink()).map_err(|_| IronfishError::InvalidParameters)?;
+
+        self.finish_read(expected_hash)
+    }
+
+    /// Check the digest of only the bytes read so far against
+    /// `expected_hash`, leaving the rest of the underlying reader unread.
+    pub(crate) fn finish_read(self, expected_hash: &str) -> Result<(), IronfishError> {
+        if bytes_to_hex(self.hasher.finalize().as_bytes()) != expected_hash {
+            return Err(IronfishError::InvalidParameters);
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: io::Read> io::Read for HashReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.hasher.update(&buf[..len]);
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HashReader, OUTPUT_PARAMS_HASH, OUTPUT_VK_HASH};
+    use crate::errors::IronfishError;
+    use bellman::groth16;
+    use bls12_381::Bls12;
+    use std::io::Read;
+
+    #[test]
+    fn test_output_params_hash() {
+        let output_bytes = include_bytes!("sapling_params/sapling-output.params");
+
+        HashReader::new(&output_bytes[..])
+            .finish(OUTPUT_PARAMS_HASH)
+            .expect("output params should match the pinned digest");
+    }
+
+    #[test]
+    fn test_partial_read_hashes_whole_file() {
+        let output_bytes = include_bytes!("sapling_params/sapling-output.params");
+
+        let mut reader = HashReader::new(&output_bytes[..]);
+        let mut prefix = [0u8; 100];
+        reader.read_exact(&mut prefix).unwrap();
+
+        reader
+            .finish(OUTPUT_PARAMS_HASH)
+            .expect("digest should cover the unread bytes too");
+    }
+
+    #[test]
+    fn test_output_vk_hash() {
+        let mut output_bytes = include_bytes!("sapling_params/sapling-output.params").to_vec();
+
+        // Only the verifying key is covered, so the proving parameters after
+        // it aren't needed to check it
+        let last = output_bytes.len() - 1;
+        output_bytes[last] ^= 1;
+
+        let mut reader = HashReader::new(&output_bytes[..]);
+        groth16::VerifyingKey::<Bls12>::read(&mut reader).expect("can read verifying key");
+        reader
+            .finish_read(OUTPUT_VK_HASH)
+            .expect("output verifying key should match the pinned digest");
+
+        output_bytes[100] ^= 1;
+        let mut reader = HashReader::new(&output_bytes[..]);
+        let _ = groth16::VerifyingKey::<Bls12>::read(&mut reader);
+        assert!(matches!(
+            reader.finish_read(OUTPUT_VK_HASH),
+            Err(IronfishError::InvalidParameters)
+        ));
+    }
+
+    #[test]
+    fn test_tampered_params_rejected() {
+        let mut output_bytes = include_bytes!("sapling_params/sapling-output.params").to_vec();
+        output_bytes[1000] ^= 1;
+
+        assert!(matches!(
+            HashReader::new(&output_bytes[..]).finish(OUTPUT_PARAMS_HASH),
+            Err(IronfishError::InvalidParameters)
+        ));
+    }
+}
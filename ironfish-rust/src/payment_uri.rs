@@ -0,0 +1,350 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Payment request URIs, which put everything needed to pay someone into a
+//! single string that can be shared as a link or QR code:
+//!
+//! `ironfish:<address>?amount=<value>&asset=<identifier>&memo=<memo>`
+//!
+//! The address is the Bech32m form of a [`PublicAddress`]. All of the
+//! parameters are optional. The asset defaults to the native asset, and the
+//! memo is percent-encoded so it can hold arbitrary bytes.
+
+use crate::{
+    assets::asset::{AssetIdentifier, NATIVE_ASSET},
+    bech32m::Network,
+    errors::IronfishError,
+    note::{Memo, Note},
+    serializing::{bytes_to_hex, hex_to_bytes},
+    PublicAddress,
+};
+
+use std::convert::TryInto;
+
+pub const PAYMENT_URI_SCHEME: &str = "ironfish";
+
+/// A request for a payment to a single recipient.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentRequest {
+    /// Address the payment should be sent to
+    pub recipient: PublicAddress,
+
+    /// Amount requested, in the smallest unit of the asset. Left empty when
+    /// the sender should choose the amount, eg: for a donation.
+    pub amount: Option<u64>,
+
+    /// Asset the payment should be made in
+    pub asset_identifier: AssetIdentifier,
+
+    /// Memo to attach to the note sent to the recipient
+    pub memo: Memo,
+}
+
+impl PaymentRequest {
+    /// Request a payment of any amount of the native asset to `recipient`.
+    pub fn new(recipient: PublicAddress) -> Self {
+        PaymentRequest {
+            recipient,
+            amount: None,
+            asset_identifier: NATIVE_ASSET,
+            memo: Memo::default(),
+        }
+    }
+
+    /// Parse a payment request URI. The recipient must be a Bech32m address
+    /// for the given network.
+    ///
+    /// Unknown parameters are ignored, unless they start with `req-`, which
+    /// marks parameters the sender is required to understand.
+    pub fn parse(uri: &str, network: Network) -> Result<Self, IronfishError> {
+        let (scheme, rest) = uri
+            .split_once(':')
+            .ok_or(IronfishError::InvalidPaymentRequest)?;
+        if !scheme.eq_ignore_ascii_case(PAYMENT_URI_SCHEME) {
+            return Err(IronfishError::InvalidPaymentRequest);
+        }
+
+        let (address, query) = match rest.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (rest, None),
+        };
+
+        let mut request = PaymentRequest::new(PublicAddress::from_bech32(address, network)?);
+
+        let mut seen_amount = false;
+        let mut seen_asset = false;
+        let mut seen_memo = false;
+
+        for parameter in query.into_iter().flat_map(|query| query.split('&')) {
+            let (name, value) = parameter
+                .split_once('=')
+                .ok_or(IronfishError::InvalidPaymentRequest)?;
+
+            match name {
+                "amount" => {
+                    mark_seen(&mut seen_amount)?;
+                    request.amount = Some(parse_amount(value)?);
+                }
+                "asset" => {
+                    mark_seen(&mut seen_asset)?;
+                    request.asset_identifier = parse_asset_identifier(value)?;
+                }
+                "memo" => {
+                    mark_seen(&mut seen_memo)?;
+                    request.memo = parse_memo(value)?;
+                }
+                _ if name.starts_with("req-") => {
+                    return Err(IronfishError::InvalidPaymentRequest);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(request)
+    }
+
+    /// Build the URI for this request, using the Bech32m form of the recipient
+    /// for the given network. Parameters with their default values are left
+    /// out to keep QR codes small.
+    pub fn to_uri(&self, network: Network) -> String {
+        let mut uri = format!(
+            "{}:{}",
+            PAYMENT_URI_SCHEME,
+            self.recipient.bech32_public_address(network)
+        );
+
+        let mut parameters = Vec::new();
+        if let Some(amount) = self.amount {
+            parameters.push(format!("amount={}", amount));
+        }
+        if self.asset_identifier != NATIVE_ASSET {
+            parameters.push(format!("asset={}", bytes_to_hex(&self.asset_identifier)));
+        }
+        let memo_length = self
+            .memo
+            .0
+            .iter()
+            .rposition(|b| *b != 0)
+            .map_or(0, |i| i + 1);
+        if memo_length > 0 {
+            parameters.push(format!(
+                "memo={}",
+                percent_encode(&self.memo.0[..memo_length])
+            ));
+        }
+
+        if !parameters.is_empty() {
+            uri.push('?');
+            uri.push_str(&parameters.join("&"));
+        }
+
+        uri
+    }
+
+    /// Create the note that pays this request, ready to be passed to
+    /// [`ProposedTransaction::add_output`](crate::ProposedTransaction::add_output).
+    ///
+    /// Fails if the request doesn't specify an amount, or with
+    /// [`IronfishError::UnsupportedAsset`] if it asks for any asset other
+    /// than [`NATIVE_ASSET`], as notes can't hold other assets yet.
+    pub fn to_note(&self) -> Result<Note, IronfishError> {
+        let amount = self.amount.ok_or(IronfishError::InvalidPaymentRequest)?;
+        if self.asset_identifier != NATIVE_ASSET {
+            return Err(IronfishError::UnsupportedAsset);
+        }
+
+        Ok(Note::new(self.recipient, amount, self.memo))
+    }
+}
+
+/// Parameters may only appear once, so a URI can't be crafted to show one
+/// value to the user and pay another.
+fn mark_seen(seen: &mut bool) -> Result<(), IronfishError> {
+    if *seen {
+        return Err(IronfishError::InvalidPaymentRequest);
+    }
+    *seen = true;
+
+    Ok(())
+}
+
+fn parse_amount(value: &str) -> Result<u64, IronfishError> {
+    // u64::from_str accepts a leading '+', which has no business in a URI
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(IronfishError::InvalidPaymentRequest);
+    }
+
+    value
+        .parse()
+        .map_err(|_| IronfishError::InvalidPaymentRequest)
+}
+
+fn parse_asset_identifier(value: &str) -> Result<AssetIdentifier, IronfishError> {
+    if value.len() != 64 {
+        return Err(IronfishError::InvalidAssetIdentifier);
+    }
+
+    hex_to_bytes(&value.to_ascii_lowercase())
+        .map_err(|_| IronfishError::InvalidAssetIdentifier)?
+        .try_into()
+        .map_err(|_| IronfishError::InvalidAssetIdentifier)
+}
+
+fn parse_memo(value: &str) -> Result<Memo, IronfishError> {
+    let bytes = percent_decode(value)?;
+    let mut memo = Memo::default();
+    if bytes.len() > memo.0.len() {
+        return Err(IronfishError::InvalidPaymentRequest);
+    }
+    memo.0[..bytes.len()].copy_from_slice(&bytes);
+
+    Ok(memo)
+}
+
+/// Encode every byte except the unreserved characters of RFC 3986.
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len() * 3);
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(*byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+fn percent_decode(value: &str) -> Result<Vec<u8>, IronfishError> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let high = bytes.next().and_then(|b| (b as char).to_digit(16));
+            let low = bytes.next().and_then(|b| (b as char).to_digit(16));
+            match (high, low) {
+                (Some(high), Some(low)) => decoded.push((high * 16 + low) as u8),
+                _ => return Err(IronfishError::InvalidPaymentRequest),
+            }
+        } else {
+            decoded.push(byte);
+        }
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::PaymentRequest;
+    use crate::{
+        assets::asset::NATIVE_ASSET, bech32m::Network, errors::IronfishError, note::Memo,
+        SaplingKey,
+    };
+
+    #[test]
+    fn test_payment_request_round_trip() {
+        let key = SaplingKey::generate_key();
+        let recipient = key.generate_public_address();
+
+        let request = PaymentRequest {
+            recipient,
+            amount: Some(u64::MAX),
+            asset_identifier: [0xab; 32],
+            memo: Memo::from("order #42 & co."),
+        };
+
+        let uri = request.to_uri(Network::Testnet);
+        assert!(uri.starts_with("ironfish:tifaddr1"));
+        assert!(uri.contains("amount=18446744073709551615"));
+        assert!(uri.contains("memo=order%20%2342%20%26%20co."));
+
+        let parsed = PaymentRequest::parse(&uri, Network::Testnet).expect("should parse");
+        assert_eq!(parsed, request);
+
+        // Notes can't hold custom assets yet
+        assert!(matches!(
+            parsed.to_note(),
+            Err(IronfishError::UnsupportedAsset)
+        ));
+
+        let native_request = PaymentRequest {
+            asset_identifier: NATIVE_ASSET,
+            ..request.clone()
+        };
+        let note = native_request.to_note().expect("should build note");
+        assert_eq!(note.owner(), recipient);
+        assert_eq!(note.value(), u64::MAX);
+        assert_eq!(note.memo(), request.memo);
+
+        // The wrong network's prefix is rejected
+        assert!(matches!(
+            PaymentRequest::parse(&uri, Network::Mainnet),
+            Err(IronfishError::InvalidBech32(_))
+        ));
+    }
+
+    #[test]
+    fn test_payment_request_defaults() {
+        let recipient = SaplingKey::generate_key().generate_public_address();
+        let address = recipient.bech32_public_address(Network::Mainnet);
+
+        let request = PaymentRequest::new(recipient);
+        let uri = request.to_uri(Network::Mainnet);
+        assert_eq!(uri, format!("ironfish:{}", address));
+
+        let parsed = PaymentRequest::parse(&uri, Network::Mainnet).expect("should parse");
+        assert_eq!(parsed.amount, None);
+        assert_eq!(parsed.asset_identifier, NATIVE_ASSET);
+        assert_eq!(parsed.memo, Memo::default());
+        assert!(matches!(
+            parsed.to_note(),
+            Err(IronfishError::InvalidPaymentRequest)
+        ));
+
+        // Unknown optional parameters are ignored
+        let uri = format!("IRONFISH:{}?amount=5&label=coffee", address);
+        let parsed = PaymentRequest::parse(&uri, Network::Mainnet).expect("should parse");
+        assert_eq!(parsed.amount, Some(5));
+    }
+
+    #[test]
+    fn test_payment_request_validation() {
+        let recipient = SaplingKey::generate_key().generate_public_address();
+        let address = recipient.bech32_public_address(Network::Mainnet);
+        let parse = |query: &str| {
+            PaymentRequest::parse(&format!("ironfish:{}?{}", address, query), Network::Mainnet)
+        };
+
+        assert!(parse("amount=18446744073709551616").is_err());
+        assert!(parse("amount=-1").is_err());
+        assert!(parse("amount=+1").is_err());
+        assert!(parse("amount=1.5").is_err());
+        assert!(parse("amount=").is_err());
+        assert!(parse("amount=1&amount=2").is_err());
+        assert!(parse("amount").is_err());
+        assert!(parse("req-expires=100").is_err());
+
+        assert!(parse(&format!("memo={}", "a".repeat(32))).is_ok());
+        assert!(parse(&format!("memo={}", "a".repeat(33))).is_err());
+        assert!(parse(&format!("memo={}", "%F0%9F%90%9F".repeat(8))).is_ok());
+        assert!(parse(&format!("memo={}", "%F0%9F%90%9F".repeat(9))).is_err());
+        assert!(parse("memo=%4").is_err());
+        assert!(parse("memo=%zz").is_err());
+
+        assert!(parse(&format!("asset={}", "ab".repeat(32))).is_ok());
+        assert!(matches!(
+            parse(&format!("asset={}", "ab".repeat(31))),
+            Err(IronfishError::InvalidAssetIdentifier)
+        ));
+        assert!(matches!(
+            parse(&format!("asset={}", "zz".repeat(32))),
+            Err(IronfishError::InvalidAssetIdentifier)
+        ));
+
+        assert!(PaymentRequest::parse(&address, Network::Mainnet).is_err());
+        assert!(PaymentRequest::parse(&format!("bitcoin:{}", address), Network::Mainnet).is_err());
+    }
+}
@@ -3,22 +3,137 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 pub use bls12_381::Scalar;
 use lazy_static::lazy_static;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
 
-use crate::Sapling;
+use crate::{errors::IronfishError, Sapling};
 
 // Loads the Sapling object once when dereferenced,
 // then reuses the reference on future calls.
 lazy_static! {
     pub static ref SAPLING: Arc<Sapling> = Arc::new(load());
+    static ref CONFIGURED_SAPLING: Mutex<Option<Sapling>> = Mutex::new(None);
+}
+
+static SAPLING_LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Configure the Sapling object that [`SAPLING`] will be loaded with, such as
+/// one built with [`Sapling::from_paths`] or a verify-only instance.
+///
+/// This has to be called before [`SAPLING`] is first dereferenced. Afterwards
+/// it returns [`IronfishError::SaplingAlreadyLoaded`].
+pub fn configure_sapling(sapling: Sapling) -> Result<(), IronfishError> {
+    let mut configured_sapling = CONFIGURED_SAPLING.lock().unwrap();
+
+    if SAPLING_LOADED.load(Ordering::SeqCst) {
+        return Err(IronfishError::SaplingAlreadyLoaded);
+    }
+
+    *configured_sapling = Some(sapling);
+
+    Ok(())
 }
 
 /// Load a sapling object configured to a BLS12 jubjub curve. This is currently
 /// the only pairing for which a jubjub curve has been defined, and is the
 /// default implementation.
 ///
-/// Provided as a convenience method so clients don't have to depend
-/// explicitly on zcash_primitives just to define a JubjubBls12 point.
+/// Uses the object passed to [`configure_sapling`] if there is one, and falls
+/// back to the parameters embedded in the binary otherwise.
 fn load() -> Sapling {
-    Sapling::load()
+    let mut configured_sapling = CONFIGURED_SAPLING.lock().unwrap();
+    SAPLING_LOADED.store(true, Ordering::SeqCst);
+
+    match configured_sapling.take() {
+        Some(sapling) => sapling,
+        None => load_default(),
+    }
+}
+
+/// The embedded parameters are compiled into the binary, so failing to parse
+/// them is a broken build rather than something a caller could recover from.
+#[cfg(feature = "embedded-params")]
+fn load_default() -> Sapling {
+    Sapling::load().expect("embedded Sapling parameters should be valid")
+}
+
+#[cfg(not(feature = "embedded-params"))]
+fn load_default() -> Sapling {
+    panic!("Sapling parameters must be provided with configure_sapling when the embedded-params feature is disabled")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{configure_sapling, SAPLING};
+    use crate::{errors::IronfishError, Sapling};
+    use bellman::groth16;
+
+    /// Verify-only instance built from the output verifying key alone, which is
+    /// the only parameter file small enough to keep in the repository.
+    fn output_verifier() -> Sapling {
+        let output_bytes = include_bytes!("sapling_params/sapling-output.params");
+        let vk = groth16::VerifyingKey::read(&output_bytes[..]).expect("can read verifying key");
+
+        Sapling {
+            spend_params: None,
+            output_params: None,
+            mint_params: None,
+            spend_verifying_key: groth16::prepare_verifying_key(&vk),
+            output_verifying_key: groth16::prepare_verifying_key(&vk),
+            mint_verifying_key: groth16::prepare_verifying_key(&vk),
+            spend_vk: vk.clone(),
+            output_vk: vk.clone(),
+            mint_vk: vk,
+        }
+    }
+
+    #[test]
+    fn test_verifier_cannot_prove() {
+        let sapling = output_verifier();
+
+        assert!(!sapling.can_prove());
+        assert!(matches!(
+            sapling.output_params(),
+            Err(IronfishError::MissingProvingParameters)
+        ));
+    }
+
+    #[test]
+    fn test_params_in_wrong_slot_rejected() {
+        let output_bytes = include_bytes!("sapling_params/sapling-output.params");
+
+        // The output verifying key doesn't match the digest pinned for the
+        // spend verifying key
+        assert!(matches!(
+            Sapling::verifier_from_readers(&output_bytes[..], &output_bytes[..], &output_bytes[..]),
+            Err(IronfishError::InvalidParameters)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_params_rejected() {
+        let output_bytes = include_bytes!("sapling_params/sapling-output.params");
+
+        assert!(matches!(
+            Sapling::from_readers(
+                &output_bytes[..100],
+                &output_bytes[..],
+                &output_bytes[..],
+                false
+            ),
+            Err(IronfishError::InvalidParameters)
+        ));
+    }
+
+    #[test]
+    fn test_configure_after_load() {
+        let _ = SAPLING.clone();
+
+        assert!(matches!(
+            configure_sapling(output_verifier()),
+            Err(IronfishError::SaplingAlreadyLoaded)
+        ));
+    }
 }
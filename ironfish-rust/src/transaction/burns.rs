@@ -0,0 +1,179 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    assets::asset::{AssetIdentifier, NATIVE_ASSET},
+    errors::IronfishError,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use std::io;
+
+/// Size of a serialized [`BurnDescription`]: the asset identifier and value.
+pub const BURN_DESCRIPTION_SIZE: usize = 32 + 8;
+
+/// Parameters used to build a burn, which destroys supply of an asset that
+/// was spent in the same transaction.
+pub struct BurnBuilder {
+    /// Identifier of the asset to be burned
+    pub(crate) asset_identifier: AssetIdentifier,
+
+    /// Amount of the asset to burn
+    pub(crate) value: u64,
+}
+
+impl BurnBuilder {
+    /// Create a new [`BurnBuilder`] attempting to burn `value` of the given
+    /// asset.
+    pub(crate) fn new(asset_identifier: AssetIdentifier, value: u64) -> Self {
+        Self {
+            asset_identifier,
+            value,
+        }
+    }
+
+    /// Load a [`BurnBuilder`] from a Read implementation.
+    pub(crate) fn read<R: io::Read>(reader: R) -> Result<Self, IronfishError> {
+        let burn = BurnDescription::read(reader)?;
+
+        Ok(Self::new(burn.asset_identifier, burn.value))
+    }
+
+    /// Stow the bytes of this [`BurnBuilder`] in the given writer.
+    pub(crate) fn write<W: io::Write>(&self, writer: W) -> Result<(), IronfishError> {
+        self.build().write(writer)
+    }
+
+    /// Construct the publicly visible [`BurnDescription`] for this burn.
+    pub(crate) fn build(&self) -> BurnDescription {
+        BurnDescription {
+            asset_identifier: self.asset_identifier,
+            value: self.value,
+        }
+    }
+}
+
+/// The publicly visible values of a burn. A burn does not need a proof; the
+/// burned value is public and is accounted for in the binding signature of the
+/// transaction, so it must be covered by spends of that asset.
+#[derive(Clone)]
+pub struct BurnDescription {
+    /// Identifier of the asset being burned
+    pub(crate) asset_identifier: AssetIdentifier,
+
+    /// Amount of the asset being burned
+    pub(crate) value: u64,
+}
+
+impl BurnDescription {
+    /// Load a [`BurnDescription`] from a Read implementation (e.g: socket,
+    /// file) This is the main entry-point when reconstructing a serialized
+    /// transaction.
+    ///
+    /// Fails for burns that [`BurnDescription::verify_value`] rejects.
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let mut asset_identifier = AssetIdentifier::default();
+        reader.read_exact(&mut asset_identifier)?;
+        let value = reader.read_u64::<LittleEndian>()?;
+
+        let burn = BurnDescription {
+            asset_identifier,
+            value,
+        };
+        burn.verify_value()?;
+
+        Ok(burn)
+    }
+
+    /// Stow the bytes of this [`BurnDescription`] in the given writer.
+    pub fn write<W: io::Write>(&self, writer: W) -> Result<(), IronfishError> {
+        self.serialize_signature_fields(writer)
+    }
+
+    pub fn asset_identifier(&self) -> &AssetIdentifier {
+        &self.asset_identifier
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Check that the burn can be accounted for in the value balance of the
+    /// transaction. The native asset has no owner that could mint it, so
+    /// burning it would only be a way to pay a fee that isn't covered by the
+    /// binding signature. Values above `i64::MAX` would wrap around to
+    /// negative balances, turning the burn into a mint.
+    pub fn verify_value(&self) -> Result<(), IronfishError> {
+        if self.asset_identifier == NATIVE_ASSET {
+            return Err(IronfishError::InvalidAssetIdentifier);
+        }
+        if self.value > i64::MAX as u64 {
+            return Err(IronfishError::IllegalValue);
+        }
+
+        Ok(())
+    }
+
+    /// Serialize the fields that are needed in calculating a signature to
+    /// the provided writer (probably a Blake2B writer)
+    pub(crate) fn serialize_signature_fields<W: io::Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), IronfishError> {
+        writer.write_all(&self.asset_identifier)?;
+        writer.write_u64::<LittleEndian>(self.value)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BurnBuilder, BurnDescription, BURN_DESCRIPTION_SIZE};
+    use crate::{assets::asset::NATIVE_ASSET, errors::IronfishError};
+
+    #[test]
+    fn test_burn_round_trip() {
+        let asset_identifier = [9u8; 32];
+        let value = 5;
+
+        let burn = BurnBuilder::new(asset_identifier, value).build();
+        assert_eq!(burn.asset_identifier(), &asset_identifier);
+        assert_eq!(burn.value(), value);
+
+        let mut serialized_description = vec![];
+        burn.write(&mut serialized_description)
+            .expect("should be able to serialize description");
+        assert_eq!(serialized_description.len(), BURN_DESCRIPTION_SIZE);
+
+        let deserialized_description = BurnDescription::read(&serialized_description[..])
+            .expect("should be able to deserialize valid description");
+        assert_eq!(deserialized_description.asset_identifier, asset_identifier);
+        assert_eq!(deserialized_description.value, value);
+    }
+
+    #[test]
+    fn test_burn_read_rejects_invalid_values() {
+        let read = |asset_identifier: [u8; 32], value: u64| {
+            let mut bytes = asset_identifier.to_vec();
+            bytes.extend_from_slice(&value.to_le_bytes());
+            BurnDescription::read(&bytes[..])
+        };
+
+        assert!(read([9u8; 32], i64::MAX as u64).is_ok());
+        assert!(matches!(
+            read([9u8; 32], i64::MAX as u64 + 1),
+            Err(IronfishError::IllegalValue)
+        ));
+        assert!(matches!(
+            read([9u8; 32], u64::MAX),
+            Err(IronfishError::IllegalValue)
+        ));
+        assert!(matches!(
+            read(NATIVE_ASSET, 1),
+            Err(IronfishError::InvalidAssetIdentifier)
+        ));
+    }
+}
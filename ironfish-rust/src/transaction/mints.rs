@@ -0,0 +1,395 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    assets::asset::{Asset, AssetIdentifier},
+    errors::IronfishError,
+    keys::SaplingKey,
+    sapling_bls12::SAPLING,
+};
+
+use bellman::{gadgets::multipack, groth16};
+use bls12_381::{Bls12, Scalar};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use group::{Curve, GroupEncoding};
+use ironfish_zkp::{
+    circuits::mint_asset::MintAsset,
+    constants::ASSET_KEY_GENERATOR,
+    redjubjub::{self, Signature},
+};
+use jubjub::{ExtendedPoint, SubgroupPoint};
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use std::io;
+
+/// Size of a serialized [`MintDescription`]: the proof, asset public key,
+/// asset identifier, value and authorizing signature.
+pub const MINT_DESCRIPTION_SIZE: usize = 192 + 32 + 32 + 8 + 64;
+
+/// Parameters used to build a proof that the owner of an asset is creating
+/// new supply of that asset.
+pub struct MintBuilder {
+    /// Asset to be minted
+    pub(crate) asset: Asset,
+
+    /// Amount of the asset to mint
+    pub(crate) value: u64,
+}
+
+impl MintBuilder {
+    /// Create a new [`MintBuilder`] attempting to mint `value` of `asset`.
+    pub(crate) fn new(asset: Asset, value: u64) -> Self {
+        Self { asset, value }
+    }
+
+    /// Load a [`MintBuilder`] from a Read implementation.
+    pub(crate) fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let asset = Asset::read(&mut reader)?;
+        let value = reader.read_u64::<LittleEndian>()?;
+        if value > i64::MAX as u64 {
+            return Err(IronfishError::IllegalValue);
+        }
+
+        Ok(Self { asset, value })
+    }
+
+    /// Stow the bytes of this [`MintBuilder`] in the given writer.
+    pub(crate) fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.asset.write(&mut writer)?;
+        writer.write_u64::<LittleEndian>(self.value)?;
+
+        Ok(())
+    }
+
+    /// Construct the [`UnsignedMintDescription`] for this mint. The returned
+    /// description still has to be signed by the asset owner once the
+    /// transaction signature hash is known.
+    ///
+    /// Verifies the proof before returning to prevent posting broken
+    /// transactions.
+    pub(crate) fn build<R: RngCore + CryptoRng>(
+        &self,
+        asset_authorization_key: jubjub::Fr,
+        rng: &mut R,
+    ) -> Result<UnsignedMintDescription, IronfishError> {
+        if ASSET_KEY_GENERATOR * asset_authorization_key != self.asset.owner {
+            return Err(IronfishError::InvalidSigningKey);
+        }
+
+        let circuit = MintAsset {
+            name: self.asset.name,
+            chain: self.asset.chain,
+            network: self.asset.network,
+            token_identifier: self.asset.token_identifier,
+            nonce: self.asset.nonce,
+            asset_authorization_key: Some(asset_authorization_key),
+        };
+
+        let proof = groth16::create_random_proof(circuit, SAPLING.mint_params()?, rng)?;
+
+        let blank_signature = {
+            let buf = [0u8; 64];
+            Signature::read(&mut buf.as_ref())?
+        };
+
+        let mint_description = MintDescription {
+            proof,
+            asset_public_key: self.asset.owner,
+            asset_identifier: self.asset.identifier,
+            value: self.value,
+            authorizing_signature: blank_signature,
+        };
+
+        mint_description.verify_proof()?;
+
+        Ok(UnsignedMintDescription { mint_description })
+    }
+}
+
+/// A [`MintDescription`] with a valid proof that has not yet been signed by
+/// the asset owner.
+pub struct UnsignedMintDescription {
+    pub(crate) mint_description: MintDescription,
+}
+
+impl UnsignedMintDescription {
+    /// Load an [`UnsignedMintDescription`] from a Read implementation.
+    pub fn read<R: io::Read>(reader: R) -> Result<Self, IronfishError> {
+        let mint_description = MintDescription::read(reader)?;
+
+        Ok(UnsignedMintDescription { mint_description })
+    }
+
+    /// Stow the bytes of this [`UnsignedMintDescription`] in the given writer.
+    pub fn write<W: io::Write>(&self, writer: W) -> Result<(), IronfishError> {
+        self.mint_description.write(writer)
+    }
+
+    pub fn sign(
+        self,
+        spender_key: &SaplingKey,
+        signature_hash: &[u8; 32],
+    ) -> Result<MintDescription, IronfishError> {
+        self.sign_with_rng(spender_key, signature_hash, &mut thread_rng())
+    }
+
+    /// Same as [`UnsignedMintDescription::sign`], but draws the signature
+    /// nonce from the given rng.
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(
+        mut self,
+        spender_key: &SaplingKey,
+        signature_hash: &[u8; 32],
+        rng: &mut R,
+    ) -> Result<MintDescription, IronfishError> {
+        let private_key = redjubjub::PrivateKey(spender_key.asset_authorization_key);
+        let public_key = redjubjub::PublicKey::from_private(&private_key, ASSET_KEY_GENERATOR);
+
+        if public_key.0 != ExtendedPoint::from(self.mint_description.asset_public_key) {
+            return Err(IronfishError::InvalidSigningKey);
+        }
+
+        let mut data_to_be_signed = [0; 64];
+        data_to_be_signed[..32].copy_from_slice(&self.mint_description.asset_public_key.to_bytes());
+        data_to_be_signed[32..].copy_from_slice(&signature_hash[..]);
+
+        self.mint_description.authorizing_signature =
+            private_key.sign(&data_to_be_signed, rng, ASSET_KEY_GENERATOR);
+
+        Ok(self.mint_description)
+    }
+}
+
+/// The publicly visible values of a mint. Minting creates new supply of an
+/// asset, so the minted value is public and is accounted for in the binding
+/// signature of the transaction.
+#[derive(Clone)]
+pub struct MintDescription {
+    /// Proof that the asset identifier was derived from the asset public key
+    /// and that the minter knows the matching asset authorization key
+    pub(crate) proof: groth16::Proof<Bls12>,
+
+    /// Public key of the asset owner. Used to verify the authorizing
+    /// signature.
+    pub(crate) asset_public_key: SubgroupPoint,
+
+    /// Identifier of the asset being minted
+    pub(crate) asset_identifier: AssetIdentifier,
+
+    /// Amount of the asset being minted
+    pub(crate) value: u64,
+
+    /// Signature of the asset owner authorizing the mint. Binds the mint to
+    /// the transaction it is a part of, so it can't be replayed in another
+    /// transaction.
+    pub(crate) authorizing_signature: Signature,
+}
+
+impl MintDescription {
+    /// Load a [`MintDescription`] from a Read implementation (e.g: socket,
+    /// file) This is the main entry-point when reconstructing a serialized
+    /// transaction.
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let proof = groth16::Proof::read(&mut reader)?;
+        let asset_public_key = {
+            let mut bytes = [0; 32];
+            reader.read_exact(&mut bytes)?;
+
+            Option::from(SubgroupPoint::from_bytes(&bytes)).ok_or(IronfishError::InvalidData)?
+        };
+        let mut asset_identifier = AssetIdentifier::default();
+        reader.read_exact(&mut asset_identifier)?;
+        let value = reader.read_u64::<LittleEndian>()?;
+        let authorizing_signature = Signature::read(&mut reader)?;
+
+        let mint_description = MintDescription {
+            proof,
+            asset_public_key,
+            asset_identifier,
+            value,
+            authorizing_signature,
+        };
+        mint_description.verify_value()?;
+
+        Ok(mint_description)
+    }
+
+    /// Stow the bytes of this [`MintDescription`] in the given writer.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.serialize_signature_fields(&mut writer)?;
+        self.authorizing_signature.write(&mut writer)?;
+
+        Ok(())
+    }
+
+    pub fn asset_identifier(&self) -> &AssetIdentifier {
+        &self.asset_identifier
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Check that the mint can be accounted for in the value balance of the
+    /// transaction. Values above `i64::MAX` would wrap around to negative
+    /// balances, turning the mint into a burn.
+    pub fn verify_value(&self) -> Result<(), IronfishError> {
+        if self.value > i64::MAX as u64 {
+            return Err(IronfishError::IllegalValue);
+        }
+
+        Ok(())
+    }
+
+    /// Verify that the signature on this mint is signing the provided input
+    /// with the asset public key on this mint.
+    pub fn verify_signature(&self, signature_hash_value: &[u8; 32]) -> Result<(), IronfishError> {
+        let public_key = redjubjub::PublicKey(self.asset_public_key.into());
+        if public_key.0.is_small_order().into() {
+            return Err(IronfishError::IsSmallOrder);
+        }
+
+        let mut data_to_be_signed = [0; 64];
+        data_to_be_signed[..32].copy_from_slice(&self.asset_public_key.to_bytes());
+        data_to_be_signed[32..].copy_from_slice(&signature_hash_value[..]);
+
+        if !public_key.verify(
+            &data_to_be_signed,
+            &self.authorizing_signature,
+            ASSET_KEY_GENERATOR,
+        ) {
+            return Err(IronfishError::VerificationFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Verify that the bellman proof confirms the asset public key and asset
+    /// identifier attached to this [`MintDescription`].
+    pub fn verify_proof(&self) -> Result<(), IronfishError> {
+        groth16::verify_proof(
+            &SAPLING.mint_verifying_key,
+            &self.proof,
+            &self.public_inputs()[..],
+        )?;
+
+        Ok(())
+    }
+
+    /// Converts the values to appropriate inputs for verifying the bellman
+    /// proof. Confirms the asset public key and asset identifier attached to
+    /// this [`MintDescription`].
+    pub fn public_inputs(&self) -> [Scalar; 4] {
+        let mut public_inputs = [Scalar::zero(); 4];
+        let p = ExtendedPoint::from(self.asset_public_key).to_affine();
+        public_inputs[0] = p.get_u();
+        public_inputs[1] = p.get_v();
+
+        let identifier = multipack::bytes_to_bits_le(&self.asset_identifier);
+        let identifier = multipack::compute_multipacking(&identifier);
+        public_inputs[2] = identifier[0];
+        public_inputs[3] = identifier[1];
+
+        public_inputs
+    }
+
+    /// Serialize the fields that are needed in calculating a signature to
+    /// the provided writer (probably a Blake2B writer)
+    pub(crate) fn serialize_signature_fields<W: io::Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), IronfishError> {
+        self.proof.write(&mut writer)?;
+        writer.write_all(&self.asset_public_key.to_bytes())?;
+        writer.write_all(&self.asset_identifier)?;
+        writer.write_u64::<LittleEndian>(self.value)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MintBuilder, MintDescription, MINT_DESCRIPTION_SIZE};
+    use crate::{assets::asset::Asset, errors::IronfishError, keys::SaplingKey};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_mint_builder() {
+        let key = SaplingKey::generate_key();
+        let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+            .expect("can create an asset");
+        let value = 5;
+
+        let mint = MintBuilder::new(asset, value);
+        let unsigned_mint = mint
+            .build(key.asset_authorization_key(), &mut thread_rng())
+            .expect("should be able to build mint proof");
+
+        let sig_hash = [0u8; 32];
+        let description = unsigned_mint
+            .sign(&key, &sig_hash)
+            .expect("should be able to sign mint");
+
+        description.verify_proof().expect("proof should check out");
+        description
+            .verify_signature(&sig_hash)
+            .expect("signature should check out");
+        assert!(description.verify_signature(&[1u8; 32]).is_err());
+
+        assert_eq!(description.asset_identifier(), asset.identifier());
+        assert_eq!(description.value(), value);
+
+        // test serialization
+        let mut serialized_description = vec![];
+        description
+            .write(&mut serialized_description)
+            .expect("should be able to serialize description");
+        assert_eq!(serialized_description.len(), MINT_DESCRIPTION_SIZE);
+
+        let deserialized_description = MintDescription::read(&serialized_description[..])
+            .expect("should be able to deserialize valid description");
+
+        assert_eq!(description.proof.a, deserialized_description.proof.a);
+        assert_eq!(description.proof.b, deserialized_description.proof.b);
+        assert_eq!(description.proof.c, deserialized_description.proof.c);
+        assert_eq!(
+            description.asset_public_key,
+            deserialized_description.asset_public_key
+        );
+        assert_eq!(
+            description.asset_identifier,
+            deserialized_description.asset_identifier
+        );
+        assert_eq!(description.value, deserialized_description.value);
+
+        let mut reserialized_description = vec![];
+        deserialized_description
+            .write(&mut reserialized_description)
+            .expect("should be able to serialize proof again");
+        assert_eq!(serialized_description, reserialized_description);
+
+        // A value that doesn't fit in an i64 is rejected
+        let value_offset = MINT_DESCRIPTION_SIZE - 64 - 8;
+        serialized_description[value_offset..value_offset + 8]
+            .copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            MintDescription::read(&serialized_description[..]),
+            Err(IronfishError::IllegalValue)
+        ));
+    }
+
+    #[test]
+    fn test_mint_builder_wrong_owner() {
+        let key = SaplingKey::generate_key();
+        let other_key = SaplingKey::generate_key();
+        let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+            .expect("can create an asset");
+
+        let mint = MintBuilder::new(asset, 5);
+
+        assert!(mint
+            .build(other_key.asset_authorization_key(), &mut thread_rng())
+            .is_err());
+    }
+}
@@ -2,14 +2,17 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use outputs::OutputBuilder;
-use spending::{SpendBuilder, UnsignedSpendDescription};
-use value_balances::ValueBalances;
+use burns::{BurnBuilder, BurnDescription, BURN_DESCRIPTION_SIZE};
+use mints::{MintBuilder, MintDescription, UnsignedMintDescription, MINT_DESCRIPTION_SIZE};
+use outputs::{OutputBuilder, OUTPUT_DESCRIPTION_SIZE};
+use spending::{SpendBuilder, UnsignedSpendDescription, SPEND_DESCRIPTION_SIZE};
+use value_balances::{signed_value, ValueBalances};
 
 use crate::{
-    assets::asset::NATIVE_ASSET,
-    errors::IronfishError,
-    keys::{PublicAddress, SaplingKey},
+    assets::asset::{value_commitment_generator, Asset, AssetIdentifier, NATIVE_ASSET},
+    chain_context::ChainContext,
+    errors::{DuplicateNullifier, IronfishError, VerificationCheck},
+    keys::{OutgoingViewKey, PublicAddress, SaplingKey},
     note::Note,
     sapling_bls12::SAPLING,
     witness::WitnessTrait,
@@ -22,15 +25,28 @@ use bls12_381::Bls12;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use group::GroupEncoding;
 use jubjub::ExtendedPoint;
-use rand::rngs::OsRng;
+use lazy_static::lazy_static;
+use rand::{
+    rngs::{OsRng, StdRng},
+    thread_rng, CryptoRng, RngCore, SeedableRng,
+};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
 
 use ironfish_zkp::{
-    constants::{VALUE_COMMITMENT_RANDOMNESS_GENERATOR, VALUE_COMMITMENT_VALUE_GENERATOR},
+    constants::VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
     redjubjub::{PrivateKey, PublicKey, Signature},
+    ProofGenerationKey,
 };
 
-use std::{io, iter, slice::Iter};
+use std::{
+    collections::HashMap,
+    io, iter,
+    slice::Iter,
+    sync::{Arc, RwLock},
+};
 
+pub mod burns;
+pub mod mints;
 pub mod outputs;
 pub mod spending;
 #[cfg(test)]
@@ -39,6 +55,64 @@ mod value_balances;
 
 const SIGNATURE_HASH_PERSONALIZATION: &[u8; 8] = b"Bnsighsh";
 const TRANSACTION_SIGNATURE_VERSION: &[u8; 1] = &[0];
+const TRANSACTION_ID_PERSONALIZATION: &[u8; 8] = b"Bntxn_id";
+const UNSIGNED_TRANSACTION_ID_PERSONALIZATION: &[u8; 8] = b"Bnutx_id";
+
+/// Set in the header of every versioned transaction. [`TransactionVersion::V1`]
+/// transactions have no header and start with their spend count instead,
+/// which never has this bit set, so the two can be told apart.
+const TRANSACTION_VERSION_FLAG: u32 = 1 << 31;
+
+/// Size of the binding signature at the end of every serialized transaction.
+const TRANSACTION_SIGNATURE_SIZE: usize = 64;
+
+/// Version of the serialized [`ProposedTransaction`] format. Bump this when
+/// changing the layout written by [`ProposedTransaction::write`].
+const PROPOSED_TRANSACTION_VERSION: u8 = 2;
+
+/// Version of the serialized [`UnsignedTransaction`] format. Bump this when
+/// changing the layout written by [`UnsignedTransaction::write`].
+const UNSIGNED_TRANSACTION_VERSION: u8 = 1;
+
+/// Number of blocks a transaction stays valid for when it's given the
+/// [`default_expiration_sequence`], eg: through
+/// [`ProposedTransaction::set_default_expiration_sequence`].
+pub const DEFAULT_EXPIRATION_SEQUENCE_DELTA: u32 = 15;
+
+lazy_static! {
+    static ref PROVING_THREAD_POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+}
+
+/// Whether a transaction with the given expiration sequence has expired by
+/// `current_sequence`, the sequence of the block it would be added to. An
+/// expiration sequence of 0 means the transaction never expires.
+pub fn is_expired_sequence(expiration_sequence: u32, current_sequence: u32) -> bool {
+    expiration_sequence != 0 && expiration_sequence <= current_sequence
+}
+
+/// Expiration sequence for a transaction created while the head of the chain
+/// is at `current_sequence`. The transaction can be added to any of the next
+/// [`DEFAULT_EXPIRATION_SEQUENCE_DELTA`] blocks.
+pub fn default_expiration_sequence(current_sequence: u32) -> u32 {
+    current_sequence.saturating_add(DEFAULT_EXPIRATION_SEQUENCE_DELTA)
+}
+
+/// Set the number of threads used to create the spend and output proofs of a
+/// transaction. Until this is called, proofs are created on the global rayon
+/// thread pool, which has one thread per CPU.
+///
+/// Can be called again to resize the pool. Transactions that are already
+/// being built keep using the pool they started with.
+pub fn configure_proving_threads(num_threads: usize) -> Result<(), IronfishError> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .thread_name(|i| format!("ironfish-prover-{}", i))
+        .build()?;
+
+    *PROVING_THREAD_POOL.write().unwrap() = Some(Arc::new(pool));
+
+    Ok(())
+}
 
 /// A collection of spend and output proofs that can be signed and verified.
 /// In general, all the spent values should add up to all the output values.
@@ -46,6 +120,12 @@ const TRANSACTION_SIGNATURE_VERSION: &[u8; 1] = &[0];
 /// The Transaction is used while the spends and outputs are being constructed,
 /// and contains working state that is used to create the transaction information.
 ///
+/// It does not hold any keys. A [`ProposedTransaction`] can either be posted
+/// in one step with the full [`SaplingKey`], or built into an
+/// [`UnsignedTransaction`] with only the [`ProofGenerationKey`] and signed
+/// separately, so the spend authorizing key never has to be on the machine
+/// creating the proofs.
+///
 /// The Transaction, below, contains the serializable version, without any
 /// secret keys or state not needed for verifying.
 pub struct ProposedTransaction {
@@ -58,95 +138,267 @@ pub struct ProposedTransaction {
     /// `outputs` in the literature.
     outputs: Vec<OutputBuilder>,
 
-    /// The balance of all the spends minus all the outputs. The difference
-    /// is the fee paid to the miner for mining the transaction.
+    /// Builders for proofs of the mints of custom assets in this transaction.
+    mints: Vec<MintBuilder>,
+
+    /// Builders for the burns of custom assets in this transaction.
+    burns: Vec<BurnBuilder>,
+
+    /// The balance of all the spends and mints minus all the outputs and
+    /// burns, per asset. The difference in the native asset is the fee paid to
+    /// the miner for mining the transaction.
     value_balances: ValueBalances,
 
     /// This is the sequence in the chain the transaction will expire at and be
     /// removed from the mempool. A value of 0 indicates the transaction will
-    /// not expire.
-    expiration_sequence: u32,
+    /// not expire. Has to be chosen before the transaction can be posted, so
+    /// a transaction never ends up without an expiration by accident.
+    expiration_sequence: Option<u32>,
 
-    /// The key used to sign the transaction and any descriptions that need
-    /// signed.
-    spender_key: SaplingKey,
+    /// Version of the transaction to build, which determines its wire format
+    /// and signature hash.
+    version: TransactionVersion,
     //
     // NOTE: If adding fields here, you may need to add fields to
-    // signature hash method, and also to Transaction.
+    // signature hash method, and also to Transaction and UnsignedTransaction.
+}
+
+impl Default for ProposedTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ProposedTransaction {
-    pub fn new(spender_key: SaplingKey) -> ProposedTransaction {
+    pub fn new() -> ProposedTransaction {
         ProposedTransaction {
             spends: vec![],
             outputs: vec![],
+            mints: vec![],
+            burns: vec![],
             value_balances: ValueBalances::new(),
-            expiration_sequence: 0,
-            spender_key,
+            expiration_sequence: None,
+            version: TransactionVersion::latest(),
         }
     }
 
-    /// Spend the note owned by spender_key at the given witness location.
-    pub fn add_spend(&mut self, note: Note, witness: &dyn WitnessTrait) {
-        self.value_balances.add(&NATIVE_ASSET, note.value() as i64);
+    /// Spend the note at the given witness location. The note has to be owned
+    /// by the key the transaction is built and signed with.
+    ///
+    /// Fails with [`IronfishError::IllegalValue`] if the note is worth more
+    /// than `i64::MAX`, or would overflow the balance of the transaction.
+    pub fn add_spend(
+        &mut self,
+        note: Note,
+        witness: &dyn WitnessTrait,
+    ) -> Result<(), IronfishError> {
+        self.add_spend_with_rng(note, witness, &mut thread_rng())
+    }
+
+    /// Same as [`ProposedTransaction::add_spend`], but draws the value
+    /// commitment randomness from the given rng.
+    pub fn add_spend_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        note: Note,
+        witness: &dyn WitnessTrait,
+        rng: &mut R,
+    ) -> Result<(), IronfishError> {
+        self.value_balances
+            .add(&NATIVE_ASSET, signed_value(note.value())?)?;
+
+        self.spends.push(SpendBuilder::new(note, witness, rng));
 
-        self.spends.push(SpendBuilder::new(note, witness));
+        Ok(())
     }
 
     /// Create a proof of a new note owned by the recipient in this
     /// transaction.
-    pub fn add_output(&mut self, note: Note) {
+    ///
+    /// Fails with [`IronfishError::IllegalValue`] if the note is worth more
+    /// than `i64::MAX`, or would overflow the balance of the transaction.
+    pub fn add_output(&mut self, note: Note) -> Result<(), IronfishError> {
+        self.add_output_with_rng(note, &mut thread_rng())
+    }
+
+    /// Same as [`ProposedTransaction::add_output`], but draws the value
+    /// commitment randomness from the given rng.
+    pub fn add_output_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        note: Note,
+        rng: &mut R,
+    ) -> Result<(), IronfishError> {
+        self.value_balances
+            .subtract(&NATIVE_ASSET, signed_value(note.value())?)?;
+
+        self.outputs.push(OutputBuilder::new(note, rng));
+
+        Ok(())
+    }
+
+    /// Create new supply of an asset owned by the signing key. Notes only hold
+    /// the native asset, so the minted value can't be sent anywhere and all of
+    /// it has to be burned again in the same transaction.
+    ///
+    /// Values above `i64::MAX` fail with [`IronfishError::IllegalValue`].
+    pub fn add_mint(&mut self, asset: Asset, value: u64) -> Result<(), IronfishError> {
+        self.value_balances
+            .add(asset.identifier(), signed_value(value)?)?;
+
+        self.mints.push(MintBuilder::new(asset, value));
+
+        Ok(())
+    }
+
+    /// Destroy supply of an asset. The burned value has to be covered by
+    /// mints of that asset in this transaction.
+    ///
+    /// The native asset can't be burned, and fails with
+    /// [`IronfishError::InvalidAssetIdentifier`].
+    pub fn add_burn(
+        &mut self,
+        asset_identifier: AssetIdentifier,
+        value: u64,
+    ) -> Result<(), IronfishError> {
+        let burn = BurnBuilder::new(asset_identifier, value);
+        burn.build().verify_value()?;
+
         self.value_balances
-            .subtract(&NATIVE_ASSET, note.value() as i64);
+            .subtract(&asset_identifier, signed_value(value)?)?;
 
-        self.outputs.push(OutputBuilder::new(note));
+        self.burns.push(burn);
+
+        Ok(())
     }
 
     /// Post the transaction. This performs a bit of validation, and signs
     /// the spends with a signature that proves the spends are part of this
     /// transaction.
     ///
+    /// This is [`ProposedTransaction::build`] followed by
+    /// [`UnsignedTransaction::sign`], for when the whole key is available.
+    ///
     /// Transaction fee is the amount the spender wants to send to the miner
     /// for mining this transaction. This has to be non-negative; sane miners
     /// wouldn't accept a transaction that takes money away from them.
     ///
+    /// For the native asset:
     /// sum(spends) - sum(outputs) - intended_transaction_fee - change = 0
     /// aka: self.value_balance - intended_transaction_fee - change = 0
+    ///
+    /// Every other asset has to balance exactly, as there is no fee and notes
+    /// can't hold it as change:
+    /// sum(mints) - sum(burns) = 0
+    ///
+    /// The transaction never expires unless an expiration was chosen with
+    /// [`ProposedTransaction::set_default_expiration_sequence`] or
+    /// [`ProposedTransaction::set_expiration_sequence`].
     pub fn post(
         &mut self,
+        spender_key: &SaplingKey,
+        change_goes_to: Option<PublicAddress>,
+        intended_transaction_fee: u64,
+    ) -> Result<Transaction, IronfishError> {
+        self.post_with_rng(
+            spender_key,
+            change_goes_to,
+            intended_transaction_fee,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Same as [`ProposedTransaction::post`], but draws all of the randomness
+    /// of the transaction from the given rng. Combined with the `_with_rng`
+    /// methods used to add spends and outputs, a seeded rng produces a
+    /// byte-identical transaction every time, which is what test vectors and
+    /// fixtures need.
+    pub fn post_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        spender_key: &SaplingKey,
         change_goes_to: Option<PublicAddress>,
         intended_transaction_fee: u64,
+        rng: &mut R,
     ) -> Result<Transaction, IronfishError> {
+        // Change goes to the default address of the spender_key, which is
+        // derived deterministically, unless told otherwise
+        let change_address =
+            change_goes_to.unwrap_or_else(|| spender_key.generate_public_address());
+
+        let unsigned_transaction = self.build_with_rng(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            Some(spender_key.asset_authorization_key()),
+            change_address,
+            intended_transaction_fee,
+            rng,
+        )?;
+
+        unsigned_transaction.sign_with_rng(spender_key, rng)
+    }
+
+    /// Create all the proofs for the transaction without signing it. Only the
+    /// [`ProofGenerationKey`] of the spender is needed, along with their
+    /// outgoing view key to encrypt the outputs.
+    ///
+    /// The `asset_authorization_key` is only needed if the transaction mints,
+    /// as the mint proof shows knowledge of it.
+    ///
+    /// Change is computed the same way as in [`ProposedTransaction::post`] and
+    /// sent to `change_goes_to`. The returned [`UnsignedTransaction`] can be
+    /// moved to the holder of the spend authorizing key to be signed.
+    ///
+    /// Like [`ProposedTransaction::post`], fails if no expiration was chosen.
+    pub fn build(
+        &mut self,
+        proof_generation_key: &ProofGenerationKey,
+        outgoing_view_key: &OutgoingViewKey,
+        asset_authorization_key: Option<jubjub::Fr>,
+        change_goes_to: PublicAddress,
+        intended_transaction_fee: u64,
+    ) -> Result<UnsignedTransaction, IronfishError> {
+        self.build_with_rng(
+            proof_generation_key,
+            outgoing_view_key,
+            asset_authorization_key,
+            change_goes_to,
+            intended_transaction_fee,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Same as [`ProposedTransaction::build`], but draws the change notes,
+    /// proofs and binding signature randomness from the given rng.
+    pub fn build_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        proof_generation_key: &ProofGenerationKey,
+        outgoing_view_key: &OutgoingViewKey,
+        asset_authorization_key: Option<jubjub::Fr>,
+        change_goes_to: PublicAddress,
+        intended_transaction_fee: u64,
+        rng: &mut R,
+    ) -> Result<UnsignedTransaction, IronfishError> {
+        let expiration_sequence = self.expiration_sequence.unwrap_or(0);
+
         let mut change_notes = vec![];
 
         for (asset_identifier, value) in self.value_balances.iter() {
             let is_native_asset = asset_identifier == &NATIVE_ASSET;
 
-            // TODO: Remove this once we actually allow custom assets
-            if !is_native_asset {
-                return Err(IronfishError::InvalidBalance);
-            }
-
             let change_amount = match is_native_asset {
-                true => *value - intended_transaction_fee as i64,
+                true => value
+                    .checked_sub(signed_value(intended_transaction_fee)?)
+                    .ok_or(IronfishError::IllegalValue)?,
                 false => *value,
             };
 
-            if change_amount < 0 {
+            if change_amount < 0 || (!is_native_asset && change_amount != 0) {
                 return Err(IronfishError::InvalidBalance);
             }
             if change_amount > 0 {
-                // TODO: The public address generated from the spender_key if
-                // change_goes_to is None should probably be associated with a
-                // known diversifier (eg: that used on other notes?)
-                // But we haven't worked out why determinacy in public addresses
-                // would be useful yet.
-                let change_address =
-                    change_goes_to.unwrap_or_else(|| self.spender_key.generate_public_address());
-                let change_note = Note::new(
-                    change_address,
+                let change_note = Note::new_with_rng(
+                    change_goes_to,
                     change_amount as u64, // we checked it was positive
                     "",
+                    rng,
                 );
 
                 change_notes.push(change_note);
@@ -154,10 +406,16 @@ impl ProposedTransaction {
         }
 
         for change_note in change_notes {
-            self.add_output(change_note);
+            self.add_output_with_rng(change_note, rng)?;
         }
 
-        self._partial_post()
+        self._partial_post(
+            proof_generation_key,
+            outgoing_view_key,
+            asset_authorization_key,
+            expiration_sequence,
+            rng,
+        )
     }
 
     /// Special case for posting a miners fee transaction. Miner fee transactions
@@ -165,7 +423,23 @@ impl ProposedTransaction {
     /// or change and therefore have a negative transaction fee. In normal use,
     /// a miner would not accept such a transaction unless it was explicitly set
     /// as the miners fee.
-    pub fn post_miners_fee(&mut self) -> Result<Transaction, IronfishError> {
+    ///
+    /// A miners fee transaction is only valid in the block it was made for,
+    /// so it never expires unless an expiration was set.
+    pub fn post_miners_fee(
+        &mut self,
+        spender_key: &SaplingKey,
+    ) -> Result<Transaction, IronfishError> {
+        self.post_miners_fee_with_rng(spender_key, &mut thread_rng())
+    }
+
+    /// Same as [`ProposedTransaction::post_miners_fee`], but draws all of the
+    /// randomness of the transaction from the given rng.
+    pub fn post_miners_fee_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        spender_key: &SaplingKey,
+        rng: &mut R,
+    ) -> Result<Transaction, IronfishError> {
         if !self.spends.is_empty() || self.outputs.len() != 1 {
             return Err(IronfishError::InvalidMinersFeeTransaction);
         }
@@ -174,109 +448,298 @@ impl ProposedTransaction {
             .get_mut(0)
             .ok_or(IronfishError::InvalidMinersFeeTransaction)?
             .set_is_miners_fee();
-        self._partial_post()
+        self._partial_post(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            None,
+            self.expiration_sequence.unwrap_or(0),
+            rng,
+        )?
+        .sign_with_rng(spender_key, rng)
     }
 
     /// Super special case for generating an illegal transaction for the genesis block.
     /// Don't bother using this anywhere else, it won't pass verification.
     #[deprecated(note = "Use only in genesis block generation")]
-    pub fn post_genesis_transaction(&self) -> Result<Transaction, IronfishError> {
-        self._partial_post()
+    pub fn post_genesis_transaction(
+        &self,
+        spender_key: &SaplingKey,
+    ) -> Result<Transaction, IronfishError> {
+        self._partial_post(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            Some(spender_key.asset_authorization_key()),
+            self.expiration_sequence.unwrap_or(0),
+            &mut thread_rng(),
+        )?
+        .sign(spender_key)
     }
 
-    /// Get the expiration sequence for this transaction
-    pub fn expiration_sequence(&self) -> u32 {
+    /// Get the expiration sequence for this transaction, if one was chosen.
+    /// Transactions without one are posted with 0, and never expire.
+    pub fn expiration_sequence(&self) -> Option<u32> {
         self.expiration_sequence
     }
 
-    /// Set the sequence to expire the transaction from the mempool.
+    /// Set the sequence to expire the transaction from the mempool. Pass 0
+    /// for a transaction that never expires.
     pub fn set_expiration_sequence(&mut self, expiration_sequence: u32) {
-        self.expiration_sequence = expiration_sequence;
+        self.expiration_sequence = Some(expiration_sequence);
     }
 
-    // Post transaction without much validation.
-    fn _partial_post(&self) -> Result<Transaction, IronfishError> {
-        // Generate binding signature keys
-        let bsig_keys = self.binding_signature_keys()?;
+    /// Expire the transaction [`DEFAULT_EXPIRATION_SEQUENCE_DELTA`] blocks
+    /// after `current_sequence`, the sequence of the head of the chain.
+    pub fn set_default_expiration_sequence(&mut self, current_sequence: u32) {
+        self.set_expiration_sequence(default_expiration_sequence(current_sequence));
+    }
 
-        // Build descriptions
-        let mut unsigned_spends = Vec::with_capacity(self.spends.len());
-        for spend in &self.spends {
-            unsigned_spends.push(spend.build(&self.spender_key)?);
+    /// Whether the transaction would already be expired if it was added to
+    /// the block at `current_sequence`. Check this before posting, as an
+    /// expired transaction will never be mined.
+    pub fn is_expired(&self, current_sequence: u32) -> bool {
+        self.expiration_sequence
+            .map_or(false, |expiration_sequence| {
+                is_expired_sequence(expiration_sequence, current_sequence)
+            })
+    }
+
+    /// Size in bytes of the [`Transaction`] this will post to, computed
+    /// without creating any proofs.
+    ///
+    /// The change is not known until the fee is chosen, so a change output is
+    /// counted whenever the balance is positive. The estimate is exact unless
+    /// the fee uses up all of the balance, in which case it is one output too
+    /// large.
+    pub fn estimated_size(&self) -> usize {
+        let change_outputs = (*self.value_balances.fee() > 0) as usize;
+
+        self.version.header_size()
+            + self.version.fields_size()
+            + self.spends.len() * SPEND_DESCRIPTION_SIZE
+            + (self.outputs.len() + change_outputs) * OUTPUT_DESCRIPTION_SIZE
+            + self.mints.len() * MINT_DESCRIPTION_SIZE
+            + self.burns.len() * BURN_DESCRIPTION_SIZE
+            + TRANSACTION_SIGNATURE_SIZE
+    }
+
+    /// Suggested fee for this transaction at `fee_rate_per_kb` ore per 1000
+    /// bytes of [`ProposedTransaction::estimated_size`]. Matches the way the
+    /// node computes fees, so never returns less than 1.
+    pub fn estimate_fee(&self, fee_rate_per_kb: u64) -> u64 {
+        let fee = fee_rate_per_kb.saturating_mul(self.estimated_size() as u64) / 1000;
+
+        fee.max(1)
+    }
+
+    /// Get the version of the transaction that will be built
+    pub fn version(&self) -> TransactionVersion {
+        self.version
+    }
+
+    /// Set the version of the transaction to build. Defaults to
+    /// [`TransactionVersion::latest`].
+    pub fn set_version(&mut self, version: TransactionVersion) {
+        self.version = version;
+    }
+
+    /// Load a [`ProposedTransaction`] from a Read implementation, so a
+    /// transaction crafted in one process (eg: a watch-only wallet) can be
+    /// proven in another.
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let version = reader.read_u8()?;
+        if version != PROPOSED_TRANSACTION_VERSION {
+            return Err(IronfishError::UnsupportedVersion);
         }
 
-        let mut output_descriptions = Vec::with_capacity(self.outputs.len());
-        for output in &self.outputs {
-            output_descriptions.push(output.build(&self.spender_key)?);
+        let num_spends = reader.read_u64::<LittleEndian>()?;
+        let num_outputs = reader.read_u64::<LittleEndian>()?;
+        let num_mints = reader.read_u64::<LittleEndian>()?;
+        let num_burns = reader.read_u64::<LittleEndian>()?;
+        let has_expiration_sequence = reader.read_u8()? != 0;
+        let expiration_sequence = reader.read_u32::<LittleEndian>()?;
+        let transaction_version = TransactionVersion::from_u8(reader.read_u8()?)?;
+
+        // Rebuild the value balances through the same methods that were used
+        // to create the transaction, rather than trusting serialized values
+        let mut proposed_transaction = ProposedTransaction::new();
+        if has_expiration_sequence {
+            proposed_transaction.expiration_sequence = Some(expiration_sequence);
         }
+        proposed_transaction.version = transaction_version;
 
-        let data_to_sign = self.transaction_signature_hash(&unsigned_spends, &output_descriptions);
+        for _ in 0..num_spends {
+            let spend = SpendBuilder::read(&mut reader)?;
+            proposed_transaction
+                .value_balances
+                .add(&NATIVE_ASSET, signed_value(spend.note.value())?)?;
+            proposed_transaction.spends.push(spend);
+        }
+
+        for _ in 0..num_outputs {
+            let output = OutputBuilder::read(&mut reader)?;
+            proposed_transaction
+                .value_balances
+                .subtract(&NATIVE_ASSET, signed_value(output.note.value())?)?;
+            proposed_transaction.outputs.push(output);
+        }
 
-        let binding_signature =
-            self.binding_signature(&bsig_keys.0, &bsig_keys.1, &data_to_sign)?;
+        for _ in 0..num_mints {
+            let mint = MintBuilder::read(&mut reader)?;
+            proposed_transaction
+                .value_balances
+                .add(mint.asset.identifier(), signed_value(mint.value)?)?;
+            proposed_transaction.mints.push(mint);
+        }
 
-        // Sign spends now that we have the data needed to be signed
-        let mut spend_descriptions = Vec::with_capacity(unsigned_spends.len());
-        for spend in unsigned_spends.drain(0..) {
-            spend_descriptions.push(spend.sign(&self.spender_key, &data_to_sign)?);
+        for _ in 0..num_burns {
+            let burn = BurnBuilder::read(&mut reader)?;
+            proposed_transaction
+                .value_balances
+                .subtract(&burn.asset_identifier, signed_value(burn.value)?)?;
+            proposed_transaction.burns.push(burn);
         }
 
-        Ok(Transaction {
-            expiration_sequence: self.expiration_sequence,
-            fee: *self.value_balances.fee(),
-            spends: spend_descriptions,
-            outputs: output_descriptions,
-            binding_signature,
-        })
+        Ok(proposed_transaction)
     }
 
-    /// Calculate a hash of the transaction data. This hash is what gets signed
-    /// by the private keys to verify that the transaction actually happened.
+    /// Store the bytes of this [`ProposedTransaction`] in the given writer.
     ///
-    /// This is called during final posting of the transaction
-    ///
-    fn transaction_signature_hash(
+    /// Unlike a [`Transaction`], this contains the plaintext notes, witnesses
+    /// and value commitment randomness. It should only be handed to a prover
+    /// or signer that is trusted with the transaction contents.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_u8(PROPOSED_TRANSACTION_VERSION)?;
+        writer.write_u64::<LittleEndian>(self.spends.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.outputs.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.mints.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.burns.len() as u64)?;
+        writer.write_u8(self.expiration_sequence.is_some() as u8)?;
+        writer.write_u32::<LittleEndian>(self.expiration_sequence.unwrap_or(0))?;
+        writer.write_u8(self.version as u8)?;
+
+        for spend in self.spends.iter() {
+            spend.write(&mut writer)?;
+        }
+
+        for output in self.outputs.iter() {
+            output.write(&mut writer)?;
+        }
+
+        for mint in self.mints.iter() {
+            mint.write(&mut writer)?;
+        }
+
+        for burn in self.burns.iter() {
+            burn.write(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
+    // Build transaction without much validation.
+    fn _partial_post<R: RngCore + CryptoRng>(
         &self,
-        spends: &[UnsignedSpendDescription],
-        outputs: &[OutputDescription],
-    ) -> [u8; 32] {
-        let mut hasher = Blake2b::new()
-            .hash_length(32)
-            .personal(SIGNATURE_HASH_PERSONALIZATION)
-            .to_state();
+        proof_generation_key: &ProofGenerationKey,
+        outgoing_view_key: &OutgoingViewKey,
+        asset_authorization_key: Option<jubjub::Fr>,
+        expiration_sequence: u32,
+        rng: &mut R,
+    ) -> Result<UnsignedTransaction, IronfishError> {
+        // V1 transactions have no room for mints or burns on the wire
+        if !self.version.has_assets() && (!self.mints.is_empty() || !self.burns.is_empty()) {
+            return Err(IronfishError::UnsupportedVersion);
+        }
 
-        hasher.update(TRANSACTION_SIGNATURE_VERSION);
-        hasher
-            .write_u32::<LittleEndian>(self.expiration_sequence)
-            .unwrap();
-        hasher
-            .write_i64::<LittleEndian>(*self.value_balances.fee())
-            .unwrap();
-        for spend in spends {
-            spend
-                .spend_proof
-                .serialize_signature_fields(&mut hasher)
-                .unwrap();
+        // Generate binding signature keys
+        let bsig_keys = self.binding_signature_keys()?;
+
+        // Each proof gets its own rng seeded from the given one, so the
+        // result doesn't depend on the order the threads run in
+        let spend_seeds = proof_seeds(self.spends.len(), rng);
+        let output_seeds = proof_seeds(self.outputs.len(), rng);
+
+        // Build descriptions. Each proof is independent of the others, so they
+        // are created in parallel and then verified together.
+        let create_proofs = || {
+            rayon::join(
+                || {
+                    self.spends
+                        .par_iter()
+                        .zip(spend_seeds)
+                        .map(|(spend, seed)| {
+                            spend.create_proof(proof_generation_key, &mut StdRng::from_seed(seed))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+                || {
+                    self.outputs
+                        .par_iter()
+                        .zip(output_seeds)
+                        .map(|(output, seed)| {
+                            output.create_proof(outgoing_view_key, &mut StdRng::from_seed(seed))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )
+        };
+
+        let proving_thread_pool = PROVING_THREAD_POOL.read().unwrap().clone();
+        let (unsigned_spends, output_descriptions) = match proving_thread_pool {
+            Some(pool) => pool.install(create_proofs),
+            None => create_proofs(),
+        };
+        let unsigned_spends = unsigned_spends?;
+        let output_descriptions = output_descriptions?;
+
+        // Verify the proofs before returning to prevent posting broken
+        // transactions
+        verify_created_proofs(&unsigned_spends, &output_descriptions)?;
+
+        let mut unsigned_mints = Vec::with_capacity(self.mints.len());
+        for mint in &self.mints {
+            let asset_authorization_key =
+                asset_authorization_key.ok_or(IronfishError::InvalidSigningKey)?;
+            unsigned_mints.push(mint.build(asset_authorization_key, rng)?);
         }
 
-        for output in outputs {
-            output.serialize_signature_fields(&mut hasher).unwrap();
+        let mut burn_descriptions = Vec::with_capacity(self.burns.len());
+        for burn in &self.burns {
+            burn_descriptions.push(burn.build());
         }
 
-        let mut hash_result = [0; 32];
-        hash_result[..].clone_from_slice(hasher.finalize().as_ref());
-        hash_result
+        let blank_signature = {
+            let buf = [0u8; 64];
+            Signature::read(&mut buf.as_ref())?
+        };
+
+        let mut unsigned_transaction = UnsignedTransaction {
+            version: self.version,
+            expiration_sequence,
+            fee: *self.value_balances.fee(),
+            spends: unsigned_spends,
+            outputs: output_descriptions,
+            mints: unsigned_mints,
+            burns: burn_descriptions,
+            binding_signature: blank_signature,
+        };
+
+        let data_to_sign = unsigned_transaction.transaction_signature_hash();
+        unsigned_transaction.binding_signature =
+            self.binding_signature(&bsig_keys.0, &bsig_keys.1, &data_to_sign, rng)?;
+
+        Ok(unsigned_transaction)
     }
 
     /// The binding signature ties up all the randomness generated with the
     /// transaction and uses it as a private key to sign all the values
     /// that were calculated as part of the transaction. This function
     /// performs the calculation and sets the value on this struct.
-    fn binding_signature(
+    fn binding_signature<R: RngCore + CryptoRng>(
         &self,
         private_key: &PrivateKey,
         public_key: &PublicKey,
         transaction_signature_hash: &[u8; 32],
+        rng: &mut R,
     ) -> Result<Signature, IronfishError> {
         let mut data_to_be_signed = [0u8; 64];
         data_to_be_signed[..32].copy_from_slice(&public_key.0.to_bytes());
@@ -284,7 +747,7 @@ impl ProposedTransaction {
 
         Ok(private_key.sign(
             &data_to_be_signed,
-            &mut OsRng,
+            rng,
             VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
         ))
     }
@@ -312,112 +775,597 @@ impl ProposedTransaction {
         let public_key =
             PublicKey::from_private(&private_key, VALUE_COMMITMENT_RANDOMNESS_GENERATOR);
 
-        check_value_consistency(
-            &public_key,
-            &binding_verification_key,
-            *self.value_balances.fee(),
-        )?;
+        // Minted and burned values are not part of any value commitment, so
+        // they are removed from the balance that the commitments are checked
+        // against
+        let mut value_balances = self.value_balances.clone();
+        for mint in &self.mints {
+            value_balances.subtract(mint.asset.identifier(), signed_value(mint.value)?)?;
+        }
+        for burn in &self.burns {
+            value_balances.add(&burn.asset_identifier, signed_value(burn.value)?)?;
+        }
+
+        check_value_consistency(&public_key, &binding_verification_key, &value_balances)?;
 
         Ok((private_key, public_key))
     }
 }
 
-/// A transaction that has been published and can be read by anyone, not storing
-/// any of the working data or private keys used in creating the proofs.
+/// A transaction with all of its proofs created that still has to be signed
+/// by the owner of the spent notes. Produced by
+/// [`ProposedTransaction::build`].
 ///
-/// This is the serializable form of a transaction.
-#[derive(Clone)]
-pub struct Transaction {
+/// Holds no secret keys, so it can be moved from the machine that created
+/// the proofs to the one holding the spend authorizing key. The binding
+/// signature is already computed, as it only depends on the randomness used
+/// in the proofs.
+pub struct UnsignedTransaction {
     /// The balance of total spends - outputs, which is the amount that the miner gets to keep
     fee: i64,
 
-    /// List of spends, or input notes, that have been destroyed.
-    spends: Vec<SpendDescription>,
+    /// Spends with valid proofs that have not been signed yet.
+    spends: Vec<UnsignedSpendDescription>,
 
     /// List of outputs, or output notes that have been created.
     outputs: Vec<OutputDescription>,
 
+    /// Mints with valid proofs that have not been signed by the asset owner yet.
+    mints: Vec<UnsignedMintDescription>,
+
+    /// List of burns, or supply of custom assets that has been destroyed.
+    burns: Vec<BurnDescription>,
+
     /// Signature calculated from accumulating randomness with all the spends
-    /// and outputs when the transaction was created.
+    /// and outputs when the transaction was built.
     binding_signature: Signature,
 
     /// This is the sequence in the chain the transaction will expire at and be
     /// removed from the mempool. A value of 0 indicates the transaction will
     /// not expire.
     expiration_sequence: u32,
+
+    /// Version of the transaction, which determines its wire format and
+    /// signature hash.
+    version: TransactionVersion,
 }
 
-impl Transaction {
-    /// Load a Transaction from a Read implementation (e.g: socket, file)
-    /// This is the main entry-point when reconstructing a serialized transaction
-    /// for verifying.
+impl UnsignedTransaction {
+    /// Sign the spends and mints with the spender's key, producing a
+    /// [`Transaction`] that can be posted.
+    ///
+    /// The signature hash is recomputed from the contents of the transaction
+    /// rather than trusted, so the signer always signs what they can inspect.
+    pub fn sign(self, spender_key: &SaplingKey) -> Result<Transaction, IronfishError> {
+        self.sign_with_rng(spender_key, &mut thread_rng())
+    }
+
+    /// Same as [`UnsignedTransaction::sign`], but draws the signature nonces
+    /// from the given rng.
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(
+        self,
+        spender_key: &SaplingKey,
+        rng: &mut R,
+    ) -> Result<Transaction, IronfishError> {
+        let data_to_sign = self.transaction_signature_hash();
+
+        let mut spend_descriptions = Vec::with_capacity(self.spends.len());
+        for spend in self.spends {
+            spend_descriptions.push(spend.sign_with_rng(spender_key, &data_to_sign, rng)?);
+        }
+
+        let mut mint_descriptions = Vec::with_capacity(self.mints.len());
+        for mint in self.mints {
+            mint_descriptions.push(mint.sign_with_rng(spender_key, &data_to_sign, rng)?);
+        }
+
+        Ok(Transaction {
+            version: self.version,
+            expiration_sequence: self.expiration_sequence,
+            fee: self.fee,
+            spends: spend_descriptions,
+            outputs: self.outputs,
+            mints: mint_descriptions,
+            burns: self.burns,
+            binding_signature: self.binding_signature,
+        })
+    }
+
+    /// Load an [`UnsignedTransaction`] from a Read implementation, so the
+    /// proofs created on one machine can be signed on another.
     pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let version = reader.read_u8()?;
+        if version != UNSIGNED_TRANSACTION_VERSION {
+            return Err(IronfishError::UnsupportedVersion);
+        }
+
         let num_spends = reader.read_u64::<LittleEndian>()?;
         let num_outputs = reader.read_u64::<LittleEndian>()?;
+        let num_mints = reader.read_u64::<LittleEndian>()?;
+        let num_burns = reader.read_u64::<LittleEndian>()?;
         let fee = reader.read_i64::<LittleEndian>()?;
         let expiration_sequence = reader.read_u32::<LittleEndian>()?;
+        let transaction_version = TransactionVersion::from_u8(reader.read_u8()?)?;
 
-        let mut spends = Vec::with_capacity(num_spends as usize);
+        let mut spends = vec![];
         for _ in 0..num_spends {
-            spends.push(SpendDescription::read(&mut reader)?);
+            spends.push(UnsignedSpendDescription::read(&mut reader)?);
         }
 
-        let mut outputs = Vec::with_capacity(num_outputs as usize);
+        let mut outputs = vec![];
         for _ in 0..num_outputs {
             outputs.push(OutputDescription::read(&mut reader)?);
         }
 
+        let mut mints = vec![];
+        for _ in 0..num_mints {
+            mints.push(UnsignedMintDescription::read(&mut reader)?);
+        }
+
+        let mut burns = vec![];
+        for _ in 0..num_burns {
+            burns.push(BurnDescription::read(&mut reader)?);
+        }
+
         let binding_signature = Signature::read(&mut reader)?;
 
-        Ok(Transaction {
+        Ok(UnsignedTransaction {
             fee,
             spends,
             outputs,
+            mints,
+            burns,
             binding_signature,
             expiration_sequence,
+            version: transaction_version,
         })
     }
 
-    /// Store the bytes of this transaction in the given writer. This is used
-    /// to serialize transactions to file or network
+    /// Store the bytes of this [`UnsignedTransaction`] in the given writer.
+    ///
+    /// The spends carry the randomness used to randomize the spender's public
+    /// key, which links them to the spender's address. Only hand this to the
+    /// signer.
     pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_u8(UNSIGNED_TRANSACTION_VERSION)?;
         writer.write_u64::<LittleEndian>(self.spends.len() as u64)?;
         writer.write_u64::<LittleEndian>(self.outputs.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.mints.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.burns.len() as u64)?;
         writer.write_i64::<LittleEndian>(self.fee)?;
         writer.write_u32::<LittleEndian>(self.expiration_sequence)?;
+        writer.write_u8(self.version as u8)?;
 
         for spend in self.spends.iter() {
             spend.write(&mut writer)?;
         }
+
         for output in self.outputs.iter() {
             output.write(&mut writer)?;
         }
 
+        for mint in self.mints.iter() {
+            mint.write(&mut writer)?;
+        }
+
+        for burn in self.burns.iter() {
+            burn.write(&mut writer)?;
+        }
+
         self.binding_signature.write(&mut writer)?;
 
         Ok(())
     }
 
-    /// Validate the transaction. Confirms that:
-    ///  *  Each of the spend proofs has the inputs it says it does
-    ///  *  Each of the output proofs has the inputs it says it has
-    ///  *  Each of the spend proofs was signed by the owner
-    ///  *  The entire transaction was signed with a binding signature
-    ///     containing those proofs (and only those proofs)
-    ///
-    pub fn verify(&self) -> Result<(), IronfishError> {
-        batch_verify_transactions(iter::once(self))
+    /// Get the transaction fee for this transaction.
+    pub fn fee(&self) -> i64 {
+        self.fee
     }
 
-    /// Get an iterator over the spends in this transaction. Each spend
-    /// is by reference
-    pub fn iter_spends(&self) -> Iter<SpendDescription> {
-        self.spends.iter()
+    /// Get the expiration sequence for this transaction
+    pub fn expiration_sequence(&self) -> u32 {
+        self.expiration_sequence
     }
 
-    pub fn spends(&self) -> &Vec<SpendDescription> {
-        &self.spends
-    }
+    pub fn outputs(&self) -> &Vec<OutputDescription> {
+        &self.outputs
+    }
+
+    pub fn burns(&self) -> &Vec<BurnDescription> {
+        &self.burns
+    }
+
+    /// Calculate a hash of the transaction data. This hash is what gets signed
+    /// by the private keys to verify that the transaction actually happened,
+    /// and is the same hash [`Transaction::transaction_signature_hash`]
+    /// reconstructs when verifying.
+    pub fn transaction_signature_hash(&self) -> [u8; 32] {
+        let mut hasher = Blake2b::new()
+            .hash_length(32)
+            .personal(SIGNATURE_HASH_PERSONALIZATION)
+            .to_state();
+
+        hasher.update(self.version.signature_hash_version());
+        hasher
+            .write_u32::<LittleEndian>(self.expiration_sequence)
+            .unwrap();
+        hasher.write_i64::<LittleEndian>(self.fee).unwrap();
+        for spend in self.spends.iter() {
+            spend
+                .spend_proof
+                .serialize_signature_fields(&mut hasher)
+                .unwrap();
+        }
+
+        for output in self.outputs.iter() {
+            output.serialize_signature_fields(&mut hasher).unwrap();
+        }
+
+        for mint in self.mints.iter() {
+            mint.mint_description
+                .serialize_signature_fields(&mut hasher)
+                .unwrap();
+        }
+
+        for burn in self.burns.iter() {
+            burn.serialize_signature_fields(&mut hasher).unwrap();
+        }
+
+        let mut hash_result = [0; 32];
+        hash_result[..].clone_from_slice(hasher.finalize().as_ref());
+        hash_result
+    }
+}
+
+/// Version of a [`Transaction`], which determines its wire format and how its
+/// signature hash is computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionVersion {
+    /// The original format, with no version header. Only read and written to
+    /// support transactions that are already on the chain.
+    V1 = 1,
+
+    /// Starts with a header holding the version, and commits to the version
+    /// in the signature hash.
+    V2 = 2,
+}
+
+impl TransactionVersion {
+    /// The version new transactions are built with
+    pub fn latest() -> Self {
+        TransactionVersion::V2
+    }
+
+    pub fn from_u8(version: u8) -> Result<Self, IronfishError> {
+        match version {
+            1 => Ok(TransactionVersion::V1),
+            2 => Ok(TransactionVersion::V2),
+            _ => Err(IronfishError::UnsupportedVersion),
+        }
+    }
+
+    /// The first byte of the signature hash. V1 transactions keep their
+    /// original prefix so their signatures still verify.
+    fn signature_hash_version(&self) -> &[u8; 1] {
+        match self {
+            TransactionVersion::V1 => TRANSACTION_SIGNATURE_VERSION,
+            TransactionVersion::V2 => &[2],
+        }
+    }
+
+    /// Size of the version header written before the rest of the
+    /// transaction.
+    fn header_size(&self) -> usize {
+        match self {
+            TransactionVersion::V1 => 0,
+            TransactionVersion::V2 => 4,
+        }
+    }
+
+    /// Size of the description counts, fee and expiration sequence written
+    /// after the version header. V1 transactions have no mints or burns, so
+    /// they don't have counts for them either.
+    fn fields_size(&self) -> usize {
+        match self {
+            TransactionVersion::V1 => 8 + 8 + 8 + 4,
+            TransactionVersion::V2 => 8 + 8 + 8 + 8 + 8 + 4,
+        }
+    }
+
+    /// Whether transactions of this version can hold mints and burns
+    fn has_assets(&self) -> bool {
+        match self {
+            TransactionVersion::V1 => false,
+            TransactionVersion::V2 => true,
+        }
+    }
+}
+
+/// Limits on the size of a [`Transaction`] read with
+/// [`Transaction::read_with_limits`].
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionLimits {
+    /// Maximum size of the serialized transaction, in bytes
+    pub max_size: usize,
+
+    /// Maximum number of spends in the transaction
+    pub max_spends: u64,
+
+    /// Maximum number of outputs in the transaction
+    pub max_outputs: u64,
+
+    /// Maximum number of mints in the transaction
+    pub max_mints: u64,
+
+    /// Maximum number of burns in the transaction
+    pub max_burns: u64,
+}
+
+impl Default for TransactionLimits {
+    /// A transaction can't be bigger than a block, which is at most 2 MB. None
+    /// of the descriptions is smaller than 40 bytes, so no more than 50,000 of
+    /// any kind fit in a block.
+    fn default() -> Self {
+        Self {
+            max_size: 2_000_000,
+            max_spends: 50_000,
+            max_outputs: 50_000,
+            max_mints: 50_000,
+            max_burns: 50_000,
+        }
+    }
+}
+
+/// A transaction that has been published and can be read by anyone, not storing
+/// any of the working data or private keys used in creating the proofs.
+///
+/// This is the serializable form of a transaction.
+#[derive(Clone)]
+pub struct Transaction {
+    /// The balance of total spends - outputs, which is the amount that the miner gets to keep
+    fee: i64,
+
+    /// List of spends, or input notes, that have been destroyed.
+    spends: Vec<SpendDescription>,
+
+    /// List of outputs, or output notes that have been created.
+    outputs: Vec<OutputDescription>,
+
+    /// List of mints, or new supply of custom assets that has been created.
+    mints: Vec<MintDescription>,
+
+    /// List of burns, or supply of custom assets that has been destroyed.
+    burns: Vec<BurnDescription>,
+
+    /// Signature calculated from accumulating randomness with all the spends
+    /// and outputs when the transaction was created.
+    binding_signature: Signature,
+
+    /// This is the sequence in the chain the transaction will expire at and be
+    /// removed from the mempool. A value of 0 indicates the transaction will
+    /// not expire.
+    expiration_sequence: u32,
+
+    /// Version of the transaction, which determines its wire format and
+    /// signature hash.
+    version: TransactionVersion,
+}
+
+impl Transaction {
+    /// Load a Transaction from a Read implementation (e.g: socket, file)
+    /// This is the main entry-point when reconstructing a serialized transaction
+    /// for verifying.
+    ///
+    /// Only use this for transactions from trusted sources, such as the
+    /// node's own database. The description counts are not checked against
+    /// any limit, so a crafted transaction can make this read for as long as
+    /// the reader has data. Transactions from untrusted sources must be read
+    /// with [`Transaction::read_with_limits`].
+    pub fn read<R: io::Read>(reader: R) -> Result<Self, IronfishError> {
+        Self::read_limited(reader, None)
+    }
+
+    /// Load a Transaction received from an untrusted source, such as a peer.
+    ///
+    /// Fails with [`IronfishError::TransactionTooLarge`] before allocating
+    /// anything if the transaction is bigger than the limits allow or claims
+    /// more descriptions than `bytes` has room for, and with
+    /// [`IronfishError::TrailingBytes`] if `bytes` holds more than a single
+    /// transaction.
+    pub fn read_with_limits(
+        bytes: &[u8],
+        limits: &TransactionLimits,
+    ) -> Result<Self, IronfishError> {
+        if bytes.len() > limits.max_size {
+            return Err(IronfishError::TransactionTooLarge);
+        }
+
+        let mut reader = bytes;
+        let transaction = Self::read_limited(&mut reader, Some((limits, bytes.len())))?;
+
+        if !reader.is_empty() {
+            return Err(IronfishError::TrailingBytes);
+        }
+
+        Ok(transaction)
+    }
+
+    /// Read a transaction, checking its description counts against `limits`
+    /// and the size of the input, if given, before allocating anything.
+    fn read_limited<R: io::Read>(
+        mut reader: R,
+        limits: Option<(&TransactionLimits, usize)>,
+    ) -> Result<Self, IronfishError> {
+        let header = reader.read_u32::<LittleEndian>()?;
+        let (version, num_spends) = if header & TRANSACTION_VERSION_FLAG == 0 {
+            // V1 transactions have no header, so this is the lower half of
+            // the spend count
+            let upper = reader.read_u32::<LittleEndian>()? as u64;
+            (TransactionVersion::V1, (upper << 32) | header as u64)
+        } else {
+            let version = u8::try_from(header & !TRANSACTION_VERSION_FLAG)
+                .map_err(|_| IronfishError::UnsupportedVersion)?;
+            let version = TransactionVersion::from_u8(version)?;
+            (version, reader.read_u64::<LittleEndian>()?)
+        };
+
+        let num_outputs = reader.read_u64::<LittleEndian>()?;
+        let (num_mints, num_burns) = if version.has_assets() {
+            (
+                reader.read_u64::<LittleEndian>()?,
+                reader.read_u64::<LittleEndian>()?,
+            )
+        } else {
+            (0, 0)
+        };
+        let fee = reader.read_i64::<LittleEndian>()?;
+        let expiration_sequence = reader.read_u32::<LittleEndian>()?;
+
+        if let Some((limits, input_size)) = limits {
+            if num_spends > limits.max_spends
+                || num_outputs > limits.max_outputs
+                || num_mints > limits.max_mints
+                || num_burns > limits.max_burns
+            {
+                return Err(IronfishError::TransactionTooLarge);
+            }
+
+            // Every description has a fixed size, so the counts also can't
+            // claim more descriptions than the rest of the input holds
+            let claimed_size = [
+                (num_spends, SPEND_DESCRIPTION_SIZE),
+                (num_outputs, OUTPUT_DESCRIPTION_SIZE),
+                (num_mints, MINT_DESCRIPTION_SIZE),
+                (num_burns, BURN_DESCRIPTION_SIZE),
+            ]
+            .iter()
+            .try_fold(
+                (version.header_size() + version.fields_size() + TRANSACTION_SIGNATURE_SIZE) as u64,
+                |size, (count, description_size)| {
+                    count
+                        .checked_mul(*description_size as u64)
+                        .and_then(|descriptions_size| size.checked_add(descriptions_size))
+                },
+            );
+            if claimed_size.map_or(true, |size| size > input_size as u64) {
+                return Err(IronfishError::TransactionTooLarge);
+            }
+        }
+
+        // Without limits the counts are unchecked, so nothing is allocated up
+        // front based on them
+        let capacity = |count: u64| if limits.is_some() { count as usize } else { 0 };
+
+        let mut spends = Vec::with_capacity(capacity(num_spends));
+        for _ in 0..num_spends {
+            spends.push(SpendDescription::read(&mut reader)?);
+        }
+
+        let mut outputs = Vec::with_capacity(capacity(num_outputs));
+        for _ in 0..num_outputs {
+            outputs.push(OutputDescription::read(&mut reader)?);
+        }
+
+        let mut mints = Vec::with_capacity(capacity(num_mints));
+        for _ in 0..num_mints {
+            mints.push(MintDescription::read(&mut reader)?);
+        }
+
+        let mut burns = Vec::with_capacity(capacity(num_burns));
+        for _ in 0..num_burns {
+            burns.push(BurnDescription::read(&mut reader)?);
+        }
+
+        let binding_signature = Signature::read(&mut reader)?;
+
+        Ok(Transaction {
+            fee,
+            spends,
+            outputs,
+            mints,
+            burns,
+            binding_signature,
+            expiration_sequence,
+            version,
+        })
+    }
+
+    /// Store the bytes of this transaction in the given writer. This is used
+    /// to serialize transactions to file or network
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.write_header(&mut writer)?;
+
+        for spend in self.spends.iter() {
+            spend.write(&mut writer)?;
+        }
+        for output in self.outputs.iter() {
+            output.write(&mut writer)?;
+        }
+        for mint in self.mints.iter() {
+            mint.write(&mut writer)?;
+        }
+        for burn in self.burns.iter() {
+            burn.write(&mut writer)?;
+        }
+
+        self.binding_signature.write(&mut writer)?;
+
+        Ok(())
+    }
+
+    /// Validate the transaction. Confirms that:
+    ///  *  Each of the spend proofs has the inputs it says it does
+    ///  *  Each of the output proofs has the inputs it says it has
+    ///  *  Each of the mint proofs has the inputs it says it has
+    ///  *  Each of the spend proofs was signed by the owner
+    ///  *  Each of the mint proofs was signed by the asset owner
+    ///  *  Each mint is worth at most `i64::MAX`
+    ///  *  Each burn is of a custom asset and worth at most `i64::MAX`
+    ///  *  The entire transaction was signed with a binding signature
+    ///     containing those proofs (and only those proofs)
+    ///
+    /// If any of these fail, the [`IronfishError::InvalidTransaction`] error
+    /// names the check and the spend, output, mint, or burn it failed on.
+    pub fn verify(&self) -> Result<(), IronfishError> {
+        batch_verify_transactions(iter::once(self))
+    }
+
+    /// Validate the transaction against the chain it is being added to, as
+    /// well as everything [`Transaction::verify`] checks. Confirms that:
+    ///  *  The transaction has not expired at the current sequence
+    ///  *  Each spend was proven against a root hash the note commitment
+    ///     tree had at the tree size in the spend
+    ///  *  None of the spent notes was already spent on the chain
+    ///
+    /// The chain checks are cheap, so they run before the proofs are
+    /// verified.
+    pub fn verify_contextual(&self, context: &impl ChainContext) -> Result<(), IronfishError> {
+        if self.is_expired(context.current_sequence()) {
+            return Err(invalid(VerificationCheck::Expired));
+        }
+
+        for (index, spend) in self.spends.iter().enumerate() {
+            if !context.is_known_anchor(&spend.root_hash, spend.tree_size) {
+                return Err(invalid(VerificationCheck::SpendAnchor(index)));
+            }
+
+            if context.is_nullifier_spent(&spend.nullifier) {
+                return Err(invalid(VerificationCheck::SpendNullifier(index)));
+            }
+        }
+
+        self.verify()
+    }
+
+    /// Get an iterator over the spends in this transaction. Each spend
+    /// is by reference
+    pub fn iter_spends(&self) -> Iter<SpendDescription> {
+        self.spends.iter()
+    }
+
+    pub fn spends(&self) -> &Vec<SpendDescription> {
+        &self.spends
+    }
 
     /// Get an iterator over the outputs in this transaction, by reference
     pub fn iter_outputs(&self) -> Iter<OutputDescription> {
@@ -428,6 +1376,24 @@ impl Transaction {
         &self.outputs
     }
 
+    /// Get an iterator over the mints in this transaction, by reference
+    pub fn iter_mints(&self) -> Iter<MintDescription> {
+        self.mints.iter()
+    }
+
+    pub fn mints(&self) -> &Vec<MintDescription> {
+        &self.mints
+    }
+
+    /// Get an iterator over the burns in this transaction, by reference
+    pub fn iter_burns(&self) -> Iter<BurnDescription> {
+        self.burns.iter()
+    }
+
+    pub fn burns(&self) -> &Vec<BurnDescription> {
+        &self.burns
+    }
+
     /// Get the transaction fee for this transaction. Miners should generally
     /// expect this to be positive (or they would lose money mining it!).
     /// The miners_fee transaction would be a special case.
@@ -435,6 +1401,28 @@ impl Transaction {
         self.fee
     }
 
+    /// Get the public value balance of each asset in this transaction.
+    ///
+    /// Every asset must balance to zero within a posted transaction apart
+    /// from the fee, which is paid out of the native asset, minted value,
+    /// which enters the transaction without a spend, and burned value, which
+    /// leaves the transaction without an output.
+    ///
+    /// Fails with [`IronfishError::IllegalValue`] if a mint or burn is worth
+    /// more than `i64::MAX`, or the balances overflow.
+    pub(crate) fn value_balances(&self) -> Result<ValueBalances, IronfishError> {
+        let mut value_balances = ValueBalances::new();
+        value_balances.add(&NATIVE_ASSET, self.fee)?;
+        for mint in self.mints.iter() {
+            value_balances.subtract(&mint.asset_identifier, signed_value(mint.value)?)?;
+        }
+        for burn in self.burns.iter() {
+            value_balances.add(&burn.asset_identifier, signed_value(burn.value)?)?;
+        }
+
+        Ok(value_balances)
+    }
+
     /// Get the transaction signature for this transaction.
     pub fn binding_signature(&self) -> &Signature {
         &self.binding_signature
@@ -445,6 +1433,87 @@ impl Transaction {
         self.expiration_sequence
     }
 
+    /// Get the version of this transaction
+    pub fn version(&self) -> TransactionVersion {
+        self.version
+    }
+
+    /// Whether the transaction has expired and can no longer be added to the
+    /// block at `current_sequence`.
+    pub fn is_expired(&self, current_sequence: u32) -> bool {
+        is_expired_sequence(self.expiration_sequence, current_sequence)
+    }
+
+    /// Identifier of this transaction, committing to all of its serialized
+    /// bytes including every signature. Unlike the signature hash, two
+    /// transactions with the same id are identical on the wire, so it can't be
+    /// changed by a third party replacing a signature.
+    pub fn id(&self) -> [u8; 32] {
+        let mut hasher = Blake2b::new()
+            .hash_length(32)
+            .personal(TRANSACTION_ID_PERSONALIZATION)
+            .to_state();
+        self.write(&mut hasher).unwrap();
+
+        let mut hash_result = [0; 32];
+        hash_result[..].clone_from_slice(hasher.finalize().as_ref());
+        hash_result
+    }
+
+    /// Identifier of this transaction without any of its signatures. Stays
+    /// the same if the transaction is signed again, so it can be used to
+    /// recognize the same transfer across differently signed copies.
+    pub fn unsigned_id(&self) -> [u8; 32] {
+        let mut hasher = Blake2b::new()
+            .hash_length(32)
+            .personal(UNSIGNED_TRANSACTION_ID_PERSONALIZATION)
+            .to_state();
+        self.write_unsigned(&mut hasher).unwrap();
+
+        let mut hash_result = [0; 32];
+        hash_result[..].clone_from_slice(hasher.finalize().as_ref());
+        hash_result
+    }
+
+    /// Write the version header, if the version has one, followed by the
+    /// description counts and the other fixed size fields.
+    fn write_header<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        if self.version != TransactionVersion::V1 {
+            writer.write_u32::<LittleEndian>(TRANSACTION_VERSION_FLAG | self.version as u32)?;
+        }
+        writer.write_u64::<LittleEndian>(self.spends.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.outputs.len() as u64)?;
+        if self.version.has_assets() {
+            writer.write_u64::<LittleEndian>(self.mints.len() as u64)?;
+            writer.write_u64::<LittleEndian>(self.burns.len() as u64)?;
+        }
+        writer.write_i64::<LittleEndian>(self.fee)?;
+        writer.write_u32::<LittleEndian>(self.expiration_sequence)?;
+
+        Ok(())
+    }
+
+    /// Write the same bytes as [`Transaction::write`], but leave out the
+    /// authorizing signatures and the binding signature.
+    fn write_unsigned<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.write_header(&mut writer)?;
+
+        for spend in self.spends.iter() {
+            spend.serialize_signature_fields(&mut writer)?;
+        }
+        for output in self.outputs.iter() {
+            output.serialize_signature_fields(&mut writer)?;
+        }
+        for mint in self.mints.iter() {
+            mint.serialize_signature_fields(&mut writer)?;
+        }
+        for burn in self.burns.iter() {
+            burn.serialize_signature_fields(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
     /// Calculate a hash of the transaction data. This hash was signed by the
     /// private keys when the transaction was constructed, and will now be
     /// reconstructed to verify the signature.
@@ -453,7 +1522,7 @@ impl Transaction {
             .hash_length(32)
             .personal(SIGNATURE_HASH_PERSONALIZATION)
             .to_state();
-        hasher.update(TRANSACTION_SIGNATURE_VERSION);
+        hasher.update(self.version.signature_hash_version());
         hasher
             .write_u32::<LittleEndian>(self.expiration_sequence)
             .unwrap();
@@ -464,19 +1533,93 @@ impl Transaction {
         for output in self.outputs.iter() {
             output.serialize_signature_fields(&mut hasher).unwrap();
         }
+        for mint in self.mints.iter() {
+            mint.serialize_signature_fields(&mut hasher).unwrap();
+        }
+        for burn in self.burns.iter() {
+            burn.serialize_signature_fields(&mut hasher).unwrap();
+        }
 
         let mut hash_result = [0; 32];
         hash_result[..].clone_from_slice(hasher.finalize().as_ref());
         hash_result
     }
 
+    /// Check everything about this transaction except for the proofs, which
+    /// are verified in batches across transactions.
+    fn verify_signatures(&self) -> Result<(), IronfishError> {
+        // Context to accumulate a signature of all the spends and outputs and
+        // guarantee they are part of this transaction, unmodified.
+        let mut binding_verification_key = ExtendedPoint::identity();
+
+        let hash_to_verify_signature = self.transaction_signature_hash();
+
+        for (index, spend) in self.spends.iter().enumerate() {
+            spend
+                .verify_value_commitment()
+                .map_err(|_| invalid(VerificationCheck::SpendValueCommitment(index)))?;
+
+            binding_verification_key += spend.value_commitment;
+
+            spend
+                .verify_signature(&hash_to_verify_signature)
+                .map_err(|_| invalid(VerificationCheck::SpendSignature(index)))?;
+        }
+
+        for (index, output) in self.outputs.iter().enumerate() {
+            output
+                .verify_value_commitment()
+                .map_err(|_| invalid(VerificationCheck::OutputValueCommitment(index)))?;
+
+            binding_verification_key -= output.merkle_note.value_commitment;
+        }
+
+        for (index, mint) in self.mints.iter().enumerate() {
+            mint.verify_signature(&hash_to_verify_signature)
+                .map_err(|_| invalid(VerificationCheck::MintSignature(index)))?;
+            mint.verify_value()
+                .map_err(|_| invalid(VerificationCheck::MintValue(index)))?;
+        }
+
+        for (index, burn) in self.burns.iter().enumerate() {
+            burn.verify_value()
+                .map_err(|_| invalid(VerificationCheck::BurnValue(index)))?;
+        }
+
+        self.verify_binding_signature(&binding_verification_key)
+            .map_err(|_| invalid(VerificationCheck::BindingSignature))
+    }
+
+    /// Verify the proofs of this transaction one at a time, to find which one
+    /// made a batch fail.
+    fn find_invalid_proof(&self) -> Result<(), IronfishError> {
+        for (index, spend) in self.spends.iter().enumerate() {
+            spend
+                .verify_proof()
+                .map_err(|_| invalid(VerificationCheck::SpendProof(index)))?;
+        }
+
+        for (index, output) in self.outputs.iter().enumerate() {
+            output
+                .verify_proof()
+                .map_err(|_| invalid(VerificationCheck::OutputProof(index)))?;
+        }
+
+        for (index, mint) in self.mints.iter().enumerate() {
+            mint.verify_proof()
+                .map_err(|_| invalid(VerificationCheck::MintProof(index)))?;
+        }
+
+        Ok(())
+    }
+
     /// Confirm that this transaction was signed by the values it contains.
     /// Called from the public verify function.
     fn verify_binding_signature(
         &self,
         binding_verification_key: &ExtendedPoint,
     ) -> Result<(), IronfishError> {
-        let value_balance_point = value_balance_to_point(self.fee)?;
+        let value_balance_point = value_balance_to_point(&self.value_balances()?)?;
 
         let public_key_point = binding_verification_key - value_balance_point;
         let public_key = PublicKey(public_key_point);
@@ -497,24 +1640,35 @@ impl Transaction {
     }
 }
 
-/// Convert the integer value to a point on the Jubjub curve, accounting for
-/// negative values
-fn value_balance_to_point(value: i64) -> Result<ExtendedPoint, IronfishError> {
-    // Can only construct edwards point on positive numbers, so need to
-    // add and possibly negate later
-    let is_negative = value.is_negative();
-    let abs = match value.checked_abs() {
-        Some(a) => a as u64,
-        None => return Err(IronfishError::IllegalValue),
-    };
-
-    let mut value_balance = VALUE_COMMITMENT_VALUE_GENERATOR * jubjub::Fr::from(abs);
+/// Convert the per-asset value balances to a point on the Jubjub curve,
+/// accounting for negative values. Each balance is committed to with the
+/// value commitment generator of its asset.
+///
+/// The balances include every public value term of the transaction: the fee,
+/// minted value, and burned value.
+fn value_balance_to_point(value_balances: &ValueBalances) -> Result<ExtendedPoint, IronfishError> {
+    let mut value_balance_point = ExtendedPoint::identity();
+
+    for (asset_identifier, value) in value_balances.iter() {
+        // Can only construct edwards point on positive numbers, so need to
+        // add and possibly negate later
+        let is_negative = value.is_negative();
+        let abs = match value.checked_abs() {
+            Some(a) => a as u64,
+            None => return Err(IronfishError::IllegalValue),
+        };
+
+        let mut value_balance =
+            value_commitment_generator(asset_identifier)? * jubjub::Fr::from(abs);
+
+        if is_negative {
+            value_balance = -value_balance;
+        }
 
-    if is_negative {
-        value_balance = -value_balance;
+        value_balance_point += value_balance;
     }
 
-    Ok(value_balance.into())
+    Ok(value_balance_point)
 }
 
 /// Confirm that balance of input and output values is consistent with
@@ -525,9 +1679,9 @@ fn value_balance_to_point(value: i64) -> Result<ExtendedPoint, IronfishError> {
 fn check_value_consistency(
     public_key: &PublicKey,
     binding_verification_key: &ExtendedPoint,
-    value: i64,
+    value_balances: &ValueBalances,
 ) -> Result<(), IronfishError> {
-    let value_balance_point = value_balance_to_point(value)?;
+    let value_balance_point = value_balance_to_point(value_balances)?;
 
     let calculated_public_key = binding_verification_key - value_balance_point;
 
@@ -538,45 +1692,224 @@ fn check_value_consistency(
     Ok(())
 }
 
-pub fn batch_verify_transactions<'a>(
-    transactions: impl IntoIterator<Item = &'a Transaction>,
+/// Draw a seed for each of `count` proofs from the given rng.
+fn proof_seeds<R: RngCore + CryptoRng>(count: usize, rng: &mut R) -> Vec<[u8; 32]> {
+    (0..count)
+        .map(|_| {
+            let mut seed = [0u8; 32];
+            rng.fill_bytes(&mut seed);
+            seed
+        })
+        .collect()
+}
+
+/// Sanity check the proofs created while building a transaction, in a batch
+/// rather than one at a time.
+fn verify_created_proofs(
+    spends: &[UnsignedSpendDescription],
+    outputs: &[OutputDescription],
 ) -> Result<(), IronfishError> {
     let mut spend_verifier = Verifier::<Bls12>::new();
     let mut output_verifier = Verifier::<Bls12>::new();
 
-    for transaction in transactions {
-        // Context to accumulate a signature of all the spends and outputs and
-        // guarantee they are part of this transaction, unmodified.
-        let mut binding_verification_key = ExtendedPoint::identity();
+    for spend in spends {
+        let public_inputs = spend.spend_proof.public_inputs();
+        spend_verifier.queue((&spend.spend_proof.proof, &public_inputs[..]));
+    }
 
-        let hash_to_verify_signature = transaction.transaction_signature_hash();
+    for output in outputs {
+        let public_inputs = output.public_inputs();
+        output_verifier.queue((&output.proof, &public_inputs[..]));
+    }
 
-        for spend in transaction.spends.iter() {
-            spend.verify_value_commitment()?;
+    spend_verifier.verify(&mut OsRng, &SAPLING.spend_vk)?;
 
-            let public_inputs = spend.public_inputs();
-            spend_verifier.queue((&spend.proof, &public_inputs[..]));
+    output_verifier.verify(&mut OsRng, &SAPLING.output_vk)?;
 
-            binding_verification_key += spend.value_commitment;
+    Ok(())
+}
+
+/// Verify a batch of transactions, such as a block, failing with the error of
+/// the first invalid transaction.
+///
+/// Runs the same checks in the same order as
+/// [`batch_verify_transactions_with_results`], so a batch fails with the same
+/// check and indexes through either of them.
+pub fn batch_verify_transactions<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> Result<(), IronfishError> {
+    batch_verify_transactions_with_results(transactions)
+        .into_iter()
+        .find(Result::is_err)
+        .unwrap_or(Ok(()))
+}
+
+/// Make sure no note is spent twice in the given transactions, whether within
+/// a single transaction or by two transactions in the same batch, such as a
+/// block. Fails with the first pair of spends that share a nullifier.
+///
+/// Only compares nullifiers, without checking that the spends are valid.
+pub fn check_duplicate_nullifiers<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> Result<(), IronfishError> {
+    let mut seen = HashMap::new();
+
+    for (transaction_index, transaction) in transactions.into_iter().enumerate() {
+        record_nullifiers(&mut seen, transaction_index, transaction)?;
+    }
+
+    Ok(())
+}
+
+/// Add the nullifiers of a transaction to `seen`, mapping each one to the
+/// spend that used it. Nothing is added if the transaction reuses a
+/// nullifier, so an invalid transaction doesn't get the transactions after
+/// it rejected as well.
+fn record_nullifiers(
+    seen: &mut HashMap<[u8; 32], (usize, usize)>,
+    transaction_index: usize,
+    transaction: &Transaction,
+) -> Result<(), IronfishError> {
+    let mut spent = HashMap::new();
+
+    for (spend_index, spend) in transaction.spends.iter().enumerate() {
+        let second = (transaction_index, spend_index);
+        let nullifier = spend.nullifier.0;
 
-            spend.verify_signature(&hash_to_verify_signature)?;
+        if let Some(first) = seen.get(&nullifier).or_else(|| spent.get(&nullifier)) {
+            return Err(IronfishError::DuplicateNullifier(DuplicateNullifier {
+                first: *first,
+                second,
+            }));
         }
 
-        for output in transaction.outputs.iter() {
-            output.verify_value_commitment()?;
+        spent.insert(nullifier, second);
+    }
+
+    seen.extend(spent);
+
+    Ok(())
+}
+
+/// Verify a batch of transactions like [`batch_verify_transactions`], but
+/// return a result for each transaction instead of one for the whole batch,
+/// so the invalid ones can be told apart from the rest.
+///
+/// Signatures, and notes spent twice within the same transaction, are
+/// checked one transaction at a time. The proofs of the transactions that
+/// pass are then verified in a single batch, and only if that fails is the
+/// batch bisected to find the transactions with invalid proofs. A batch of
+/// valid transactions costs about the same as verifying all of its proofs
+/// in a single batch.
+///
+/// Notes spent by more than one transaction are attributed to the later one,
+/// but only among transactions that are otherwise valid. Anyone can copy a
+/// nullifier into a transaction with a bogus proof, which must not get the
+/// transaction that really spends the note rejected.
+pub fn batch_verify_transactions_with_results<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> Vec<Result<(), IronfishError>> {
+    let transactions: Vec<&Transaction> = transactions.into_iter().collect();
+
+    let mut results: Vec<Result<(), IronfishError>> = transactions
+        .iter()
+        .enumerate()
+        .map(|(index, transaction)| {
+            transaction.verify_signatures()?;
+            record_nullifiers(&mut HashMap::new(), index, transaction)
+        })
+        .collect();
+
+    let candidates: Vec<(usize, &Transaction)> = transactions
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(index, _)| results[*index].is_ok())
+        .collect();
+
+    bisect_proofs(&candidates, &mut results);
+
+    let mut seen = HashMap::new();
+    for (index, transaction) in transactions.into_iter().enumerate() {
+        if results[index].is_ok() {
+            results[index] = record_nullifiers(&mut seen, index, transaction);
+        }
+    }
+
+    results
+}
+
+/// Verify the proofs of the given transactions in a batch. If the batch fails,
+/// split it in half and verify each half, until the transactions with invalid
+/// proofs are isolated. Their errors are stored in `results`, at the index
+/// they were paired with.
+fn bisect_proofs(
+    transactions: &[(usize, &Transaction)],
+    results: &mut [Result<(), IronfishError>],
+) {
+    if transactions.is_empty() {
+        return;
+    }
+
+    if let Err(e) = batch_verify_proofs(transactions.iter().map(|(_, transaction)| *transaction)) {
+        if let [(index, _)] = transactions {
+            results[*index] = Err(e);
+            return;
+        }
+
+        let (left, right) = transactions.split_at(transactions.len() / 2);
+        bisect_proofs(left, results);
+        bisect_proofs(right, results);
+    }
+}
+
+/// Verify all the spend, output, and mint proofs of the given transactions in
+/// a single batch per circuit.
+fn batch_verify_proofs<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> Result<(), IronfishError> {
+    let transactions: Vec<&Transaction> = transactions.into_iter().collect();
+
+    let mut spend_verifier = Verifier::<Bls12>::new();
+    let mut output_verifier = Verifier::<Bls12>::new();
+    let mut mint_verifier = Verifier::<Bls12>::new();
 
+    for transaction in transactions.iter() {
+        for spend in transaction.spends.iter() {
+            let public_inputs = spend.public_inputs();
+            spend_verifier.queue((&spend.proof, &public_inputs[..]));
+        }
+
+        for output in transaction.outputs.iter() {
             let public_inputs = output.public_inputs();
             output_verifier.queue((&output.proof, &public_inputs[..]));
-
-            binding_verification_key -= output.merkle_note.value_commitment;
         }
 
-        transaction.verify_binding_signature(&binding_verification_key)?;
+        for mint in transaction.mints.iter() {
+            let public_inputs = mint.public_inputs();
+            mint_verifier.queue((&mint.proof, &public_inputs[..]));
+        }
     }
 
-    spend_verifier.verify(&mut OsRng, &SAPLING.spend_params.vk)?;
+    let result = spend_verifier
+        .verify(&mut OsRng, &SAPLING.spend_vk)
+        .and_then(|_| output_verifier.verify(&mut OsRng, &SAPLING.output_vk))
+        .and_then(|_| mint_verifier.verify(&mut OsRng, &SAPLING.mint_vk));
+
+    if let Err(e) = result {
+        // The batch doesn't say which proof failed. For a single transaction,
+        // which is also where bisecting a batch ends up, check them one at a
+        // time to name the failing one.
+        if let [transaction] = transactions[..] {
+            transaction.find_invalid_proof()?;
+        }
 
-    output_verifier.verify(&mut OsRng, &SAPLING.output_params.vk)?;
+        return Err(e.into());
+    }
 
     Ok(())
 }
+
+fn invalid(check: VerificationCheck) -> IronfishError {
+    IronfishError::InvalidTransaction(check)
+}
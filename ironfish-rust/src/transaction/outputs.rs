@@ -3,21 +3,30 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
-    errors::IronfishError, keys::SaplingKey, merkle_note::MerkleNote, note::Note,
+    errors::IronfishError,
+    keys::OutgoingViewKey,
+    merkle_note::{MerkleNote, MERKLE_NOTE_SIZE},
+    note::Note,
     sapling_bls12::SAPLING,
+    serializing::read_scalar,
 };
 
 use bellman::groth16;
 use bls12_381::{Bls12, Scalar};
-use ff::Field;
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use ff::{Field, PrimeField};
 use group::Curve;
 use ironfish_zkp::proofs::Output;
 use ironfish_zkp::ValueCommitment;
 use jubjub::ExtendedPoint;
-use rand::thread_rng;
+use rand::{CryptoRng, RngCore};
 
 use std::io;
 
+/// Size of a serialized [`OutputDescription`]: the proof followed by the
+/// [`MerkleNote`].
+pub const OUTPUT_DESCRIPTION_SIZE: usize = 192 + MERKLE_NOTE_SIZE;
+
 /// Parameters used when constructing proof that a new note exists. The owner
 /// of this note is the recipient of funds in a transaction. The note is signed
 /// with the owners public key so only they can read it.
@@ -37,10 +46,10 @@ pub struct OutputBuilder {
 
 impl OutputBuilder {
     /// Create a new [`OutputBuilder`] attempting to create a note.
-    pub(crate) fn new(note: Note) -> Self {
+    pub(crate) fn new<R: RngCore + CryptoRng>(note: Note, rng: &mut R) -> Self {
         let value_commitment = ValueCommitment {
             value: note.value,
-            randomness: jubjub::Fr::random(thread_rng()),
+            randomness: jubjub::Fr::random(rng),
         };
 
         Self {
@@ -50,6 +59,34 @@ impl OutputBuilder {
         }
     }
 
+    /// Load an [`OutputBuilder`] from a Read implementation. Used when a
+    /// [`crate::ProposedTransaction`] is passed between processes.
+    pub(crate) fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let note = Note::read(&mut reader)?;
+        let value_commitment = ValueCommitment {
+            value: note.value,
+            randomness: read_scalar(&mut reader)?,
+        };
+        let is_miners_fee = reader.read_u8()? != 0;
+
+        Ok(Self {
+            note,
+            value_commitment,
+            is_miners_fee,
+        })
+    }
+
+    /// Stow the bytes of this [`OutputBuilder`] in the given writer. This
+    /// includes the plaintext note, so it should only be sent to parties
+    /// trusted to see it.
+    pub(crate) fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.note.write(&mut writer)?;
+        writer.write_all(self.value_commitment.randomness.to_repr().as_ref())?;
+        writer.write_u8(self.is_miners_fee as u8)?;
+
+        Ok(())
+    }
+
     /// Sets the `is_miners_fee` flag to true, indicating that this output is to
     /// be used for a miner's fee transaction.
     pub(crate) fn set_is_miners_fee(&mut self) {
@@ -71,11 +108,30 @@ impl OutputBuilder {
     ///
     /// Verifies the proof before returning to prevent posting broken
     /// transactions.
-    pub(crate) fn build(
+    pub(crate) fn build<R: RngCore + CryptoRng>(
         &self,
-        spender_key: &SaplingKey,
+        outgoing_view_key: &OutgoingViewKey,
+        rng: &mut R,
     ) -> Result<OutputDescription, IronfishError> {
-        let diffie_hellman_keys = self.note.owner.generate_diffie_hellman_keys();
+        let output_proof = self.create_proof(outgoing_view_key, rng)?;
+
+        output_proof.verify_proof()?;
+
+        Ok(output_proof)
+    }
+
+    /// Same as [`OutputBuilder::build`], but without verifying the proof. The
+    /// caller is responsible for verifying it, usually in a batch with the
+    /// other proofs of the transaction.
+    pub(crate) fn create_proof<R: RngCore + CryptoRng>(
+        &self,
+        outgoing_view_key: &OutgoingViewKey,
+        rng: &mut R,
+    ) -> Result<OutputDescription, IronfishError> {
+        let diffie_hellman_keys = self
+            .note
+            .owner
+            .generate_diffie_hellman_keys_with_rng(&mut *rng);
 
         let circuit = Output {
             value_commitment: Some(self.value_commitment.clone()),
@@ -84,25 +140,20 @@ impl OutputBuilder {
             esk: Some(diffie_hellman_keys.0),
         };
 
-        let proof =
-            groth16::create_random_proof(circuit, &SAPLING.output_params, &mut thread_rng())?;
+        let proof = groth16::create_random_proof(circuit, SAPLING.output_params()?, rng)?;
 
         let merkle_note = if self.is_miners_fee {
             MerkleNote::new_for_miners_fee(&self.note, &self.value_commitment, &diffie_hellman_keys)
         } else {
             MerkleNote::new(
-                spender_key,
+                outgoing_view_key,
                 &self.note,
                 &self.value_commitment,
                 &diffie_hellman_keys,
             )
         };
 
-        let output_proof = OutputDescription { proof, merkle_note };
-
-        output_proof.verify_proof()?;
-
-        Ok(output_proof)
+        Ok(OutputDescription { proof, merkle_note })
     }
 }
 
@@ -205,11 +256,12 @@ impl OutputDescription {
 
 #[cfg(test)]
 mod test {
-    use super::{OutputBuilder, OutputDescription};
+    use super::{OutputBuilder, OutputDescription, OUTPUT_DESCRIPTION_SIZE};
     use crate::{keys::SaplingKey, merkle_note::NOTE_ENCRYPTION_MINER_KEYS, note::Note};
     use ff::PrimeField;
     use group::Curve;
     use jubjub::ExtendedPoint;
+    use rand::thread_rng;
 
     #[test]
     /// Test to confirm that creating an output with the `is_miners_fee` flag
@@ -218,11 +270,11 @@ mod test {
         let spender_key = SaplingKey::generate_key();
         let note = Note::new(spender_key.generate_public_address(), 42, "");
 
-        let mut output = OutputBuilder::new(note);
+        let mut output = OutputBuilder::new(note, &mut thread_rng());
         output.set_is_miners_fee();
 
         let proof = output
-            .build(&spender_key)
+            .build(spender_key.outgoing_view_key(), &mut thread_rng())
             .expect("should be able to build output proof");
 
         assert_eq!(
@@ -236,10 +288,10 @@ mod test {
         let spender_key = SaplingKey::generate_key();
         let note = Note::new(spender_key.generate_public_address(), 42, "");
 
-        let output = OutputBuilder::new(note);
+        let output = OutputBuilder::new(note, &mut thread_rng());
 
         let proof = output
-            .build(&spender_key)
+            .build(spender_key.outgoing_view_key(), &mut thread_rng())
             .expect("should be able to build output proof");
 
         assert_ne!(
@@ -253,9 +305,9 @@ mod test {
         let spender_key = SaplingKey::generate_key();
         let note = Note::new(spender_key.generate_public_address(), 42, "");
 
-        let output = OutputBuilder::new(note);
+        let output = OutputBuilder::new(note, &mut thread_rng());
         let proof = output
-            .build(&spender_key)
+            .build(spender_key.outgoing_view_key(), &mut thread_rng())
             .expect("Should be able to build output proof");
         proof.verify_proof().expect("proof should check out");
 
@@ -264,6 +316,7 @@ mod test {
         proof
             .write(&mut serialized_proof)
             .expect("Should be able to serialize proof");
+        assert_eq!(serialized_proof.len(), OUTPUT_DESCRIPTION_SIZE);
         let read_back_proof: OutputDescription =
             OutputDescription::read(&mut serialized_proof[..].as_ref())
                 .expect("Should be able to deserialize valid proof");
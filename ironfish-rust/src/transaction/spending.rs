@@ -20,11 +20,16 @@ use ff::{Field, PrimeField};
 use group::{Curve, GroupEncoding};
 use ironfish_zkp::proofs::Spend;
 use ironfish_zkp::{constants::SPENDING_KEY_GENERATOR, redjubjub::Signature};
-use ironfish_zkp::{redjubjub, Nullifier, ValueCommitment};
+use ironfish_zkp::{redjubjub, Nullifier, ProofGenerationKey, ValueCommitment};
 use jubjub::ExtendedPoint;
-use rand::thread_rng;
+use rand::{thread_rng, CryptoRng, RngCore};
 use std::io;
 
+/// Size of a serialized [`SpendDescription`]: the proof, value commitment,
+/// randomized public key, root hash, tree size, nullifier and authorizing
+/// signature.
+pub const SPEND_DESCRIPTION_SIZE: usize = 192 + 32 + 32 + 32 + 4 + 32 + 64;
+
 /// Parameters used when constructing proof that the spender owns a note with
 /// a given value.
 ///
@@ -56,10 +61,14 @@ impl SpendBuilder {
     /// This is the only time this API thinks about the merkle tree. The witness
     /// contains the root-hash at the time the witness was created and the path
     /// to verify the location of that note in the tree.
-    pub(crate) fn new(note: Note, witness: &dyn WitnessTrait) -> Self {
+    pub(crate) fn new<R: RngCore + CryptoRng>(
+        note: Note,
+        witness: &dyn WitnessTrait,
+        rng: &mut R,
+    ) -> Self {
         let value_commitment = ValueCommitment {
             value: note.value,
-            randomness: jubjub::Fr::random(thread_rng()),
+            randomness: jubjub::Fr::random(rng),
         };
 
         SpendBuilder {
@@ -80,24 +89,106 @@ impl SpendBuilder {
         ExtendedPoint::from(self.value_commitment.commitment())
     }
 
-    /// Sign this spend with the private key, and return a [`SpendDescription`]
-    /// suitable for serialization.
+    /// Load a [`SpendBuilder`] from a Read implementation. Used when a
+    /// [`crate::ProposedTransaction`] is passed between processes.
+    pub(crate) fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let note = Note::read(&mut reader)?;
+        let value_commitment = ValueCommitment {
+            value: note.value,
+            randomness: read_scalar(&mut reader)?,
+        };
+        let root_hash = read_scalar(&mut reader)?;
+        let tree_size = reader.read_u32::<LittleEndian>()?;
+        let witness_position = reader.read_u64::<LittleEndian>()?;
+
+        let auth_path_len = reader.read_u8()?;
+        let mut auth_path = Vec::with_capacity(auth_path_len as usize);
+        for _ in 0..auth_path_len {
+            let node = match reader.read_u8()? {
+                0 => None,
+                1 => {
+                    let hash = read_scalar(&mut reader)?;
+                    let is_right = reader.read_u8()? != 0;
+                    Some((hash, is_right))
+                }
+                _ => return Err(IronfishError::InvalidData),
+            };
+            auth_path.push(node);
+        }
+
+        Ok(SpendBuilder {
+            note,
+            value_commitment,
+            root_hash,
+            tree_size,
+            witness_position,
+            auth_path,
+        })
+    }
+
+    /// Stow the bytes of this [`SpendBuilder`] in the given writer. This
+    /// includes the plaintext note and the value commitment randomness, so it
+    /// should only be sent to parties trusted to see them.
+    pub(crate) fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.note.write(&mut writer)?;
+        writer.write_all(self.value_commitment.randomness.to_repr().as_ref())?;
+        writer.write_all(self.root_hash.to_repr().as_ref())?;
+        writer.write_u32::<LittleEndian>(self.tree_size)?;
+        writer.write_u64::<LittleEndian>(self.witness_position)?;
+
+        writer.write_u8(self.auth_path.len() as u8)?;
+        for node in &self.auth_path {
+            match node {
+                None => writer.write_u8(0)?,
+                Some((hash, is_right)) => {
+                    writer.write_u8(1)?;
+                    writer.write_all(hash.to_repr().as_ref())?;
+                    writer.write_u8(*is_right as u8)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the proof for this spend, and return an
+    /// [`UnsignedSpendDescription`] that can be signed by the holder of the
+    /// spend authorizing key once the transaction signature hash is known.
+    ///
+    /// Only the proof generation key is needed, so this can be done on a
+    /// machine that never sees the spend authorizing key.
     ///
     /// Verifies the proof before returning to prevent posting broken
     /// transactions
-    pub(crate) fn build(
+    pub(crate) fn build<R: RngCore + CryptoRng>(
         &self,
-        spender_key: &SaplingKey,
+        proof_generation_key: &ProofGenerationKey,
+        rng: &mut R,
+    ) -> Result<UnsignedSpendDescription, IronfishError> {
+        let unsigned_spend = self.create_proof(proof_generation_key, rng)?;
+
+        unsigned_spend.spend_proof.verify_proof()?;
+
+        Ok(unsigned_spend)
+    }
+
+    /// Same as [`SpendBuilder::build`], but without verifying the proof. The
+    /// caller is responsible for verifying it, usually in a batch with the
+    /// other proofs of the transaction.
+    pub(crate) fn create_proof<R: RngCore + CryptoRng>(
+        &self,
+        proof_generation_key: &ProofGenerationKey,
+        rng: &mut R,
     ) -> Result<UnsignedSpendDescription, IronfishError> {
         // Used to add randomness to signature generation without leaking the
         // key. Referred to as `ar` in the literature.
-        let public_key_randomness = jubjub::Fr::random(thread_rng());
+        let public_key_randomness = jubjub::Fr::random(&mut *rng);
 
         let value_commitment_point = self.value_commitment_point();
 
         let circuit = Spend {
             value_commitment: Some(self.value_commitment.clone()),
-            proof_generation_key: Some(spender_key.sapling_proof_generation_key()),
+            proof_generation_key: Some(proof_generation_key.clone()),
             payment_address: Some(self.note.owner.sapling_payment_address()),
             auth_path: self.auth_path.clone(),
             commitment_randomness: Some(self.note.randomness),
@@ -107,18 +198,20 @@ impl SpendBuilder {
 
         // Proof that the spend was valid and successful for the provided owner
         // and note.
-        let proof =
-            groth16::create_random_proof(circuit, &SAPLING.spend_params, &mut thread_rng())?;
+        let proof = groth16::create_random_proof(circuit, SAPLING.spend_params()?, rng)?;
 
         // The public key after randomization has been applied. This is used
         // during signature verification. Referred to as `rk` in the literature
         // Calculated from the authorizing key and the public_key_randomness.
-        let randomized_public_key = redjubjub::PublicKey(spender_key.authorizing_key.into())
+        let randomized_public_key = redjubjub::PublicKey(proof_generation_key.ak.into())
             .randomize(public_key_randomness, SPENDING_KEY_GENERATOR);
 
         // Bytes to be placed into the nullifier set to verify whether this note
         // has been previously spent.
-        let nullifier = self.note.nullifier(spender_key, self.witness_position);
+        let nullifier = self.note.sapling_note().nf(
+            &proof_generation_key.to_viewing_key(),
+            self.witness_position,
+        );
 
         let blank_sig = {
             let buf = [0u8; 64];
@@ -135,8 +228,6 @@ impl SpendBuilder {
             authorizing_signature: blank_sig,
         };
 
-        spend_proof.verify_proof()?;
-
         Ok(UnsignedSpendDescription {
             public_key_randomness,
             spend_proof,
@@ -150,10 +241,41 @@ pub struct UnsignedSpendDescription {
 }
 
 impl UnsignedSpendDescription {
+    /// Load an [`UnsignedSpendDescription`] from a Read implementation.
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let public_key_randomness = read_scalar(&mut reader)?;
+        let spend_proof = SpendDescription::read(&mut reader)?;
+
+        Ok(UnsignedSpendDescription {
+            public_key_randomness,
+            spend_proof,
+        })
+    }
+
+    /// Stow the bytes of this [`UnsignedSpendDescription`] in the given
+    /// writer.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_all(self.public_key_randomness.to_repr().as_ref())?;
+        self.spend_proof.write(&mut writer)?;
+
+        Ok(())
+    }
+
     pub fn sign(
+        self,
+        spender_key: &SaplingKey,
+        signature_hash: &[u8; 32],
+    ) -> Result<SpendDescription, IronfishError> {
+        self.sign_with_rng(spender_key, signature_hash, &mut thread_rng())
+    }
+
+    /// Same as [`UnsignedSpendDescription::sign`], but draws the signature
+    /// nonce from the given rng.
+    pub fn sign_with_rng<R: RngCore + CryptoRng>(
         mut self,
         spender_key: &SaplingKey,
         signature_hash: &[u8; 32],
+        rng: &mut R,
     ) -> Result<SpendDescription, IronfishError> {
         let private_key = redjubjub::PrivateKey(spender_key.spend_authorizing_key);
         let randomized_private_key = private_key.randomize(self.public_key_randomness);
@@ -169,11 +291,8 @@ impl UnsignedSpendDescription {
             .copy_from_slice(&self.spend_proof.randomized_public_key.0.to_bytes());
         data_to_be_signed[32..].copy_from_slice(&signature_hash[..]);
 
-        self.spend_proof.authorizing_signature = randomized_private_key.sign(
-            &data_to_be_signed,
-            &mut thread_rng(),
-            SPENDING_KEY_GENERATOR,
-        );
+        self.spend_proof.authorizing_signature =
+            randomized_private_key.sign(&data_to_be_signed, rng, SPENDING_KEY_GENERATOR);
 
         Ok(self.spend_proof)
     }
@@ -393,7 +512,7 @@ fn serialize_signature_fields<W: io::Write>(
 
 #[cfg(test)]
 mod test {
-    use super::{SpendBuilder, SpendDescription};
+    use super::{SpendBuilder, SpendDescription, SPEND_DESCRIPTION_SIZE};
     use crate::{keys::SaplingKey, note::Note, test_util::make_fake_witness};
     use group::Curve;
     use rand::prelude::*;
@@ -409,13 +528,15 @@ mod test {
         let note = Note::new(public_address, note_randomness, "");
         let witness = make_fake_witness(&note);
 
-        let spend = SpendBuilder::new(note, &witness);
+        let spend = SpendBuilder::new(note, &witness, &mut thread_rng());
 
         // signature comes from transaction, normally
         let mut sig_hash = [0u8; 32];
         thread_rng().fill(&mut sig_hash[..]);
 
-        let unsigned_proof = spend.build(&key).expect("should be able to build proof");
+        let unsigned_proof = spend
+            .build(&key.sapling_proof_generation_key(), &mut thread_rng())
+            .expect("should be able to build proof");
         let proof = unsigned_proof
             .sign(&key, &sig_hash)
             .expect("should be able to sign proof");
@@ -436,6 +557,7 @@ mod test {
         proof
             .write(&mut serialized_proof)
             .expect("should be able to serialize proof");
+        assert_eq!(serialized_proof.len(), SPEND_DESCRIPTION_SIZE);
         let read_back_proof: SpendDescription =
             SpendDescription::read(&mut serialized_proof[..].as_ref())
                 .expect("should be able to deserialize valid proof");
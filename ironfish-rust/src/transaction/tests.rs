@@ -3,13 +3,29 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 #[cfg(test)]
-use super::{ProposedTransaction, Transaction};
+use super::{
+    batch_verify_transactions, batch_verify_transactions_with_results, bisect_proofs,
+    burns::BurnDescription, check_duplicate_nullifiers, configure_proving_threads,
+    default_expiration_sequence, is_expired_sequence, value_balance_to_point,
+    value_balances::ValueBalances, ProposedTransaction, Transaction, TransactionLimits,
+    TransactionVersion, UnsignedTransaction, DEFAULT_EXPIRATION_SEQUENCE_DELTA,
+    TRANSACTION_VERSION_FLAG,
+};
 use crate::{
-    keys::SaplingKey, merkle_note::NOTE_ENCRYPTION_MINER_KEYS, note::Note,
+    assets::asset::{Asset, NATIVE_ASSET},
+    chain_context::ChainContext,
+    errors::{DuplicateNullifier, IronfishError, VerificationCheck},
+    keys::SaplingKey,
+    merkle_note::NOTE_ENCRYPTION_MINER_KEYS,
+    note::Note,
     test_util::make_fake_witness,
 };
 
-use ironfish_zkp::redjubjub::Signature;
+use bls12_381::Scalar;
+use byteorder::{LittleEndian, WriteBytesExt};
+use ironfish_zkp::{redjubjub::Signature, Nullifier};
+use jubjub::ExtendedPoint;
+use rand::{rngs::StdRng, SeedableRng};
 
 #[test]
 fn test_transaction() {
@@ -21,10 +37,11 @@ fn test_transaction() {
     let witness = make_fake_witness(&in_note);
     let _witness2 = make_fake_witness(&in_note2);
 
-    let mut transaction = ProposedTransaction::new(spender_key);
-    transaction.add_spend(in_note, &witness);
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
     assert_eq!(transaction.spends.len(), 1);
-    transaction.add_output(out_note);
+    transaction.add_output(out_note).unwrap();
     assert_eq!(transaction.outputs.len(), 1);
 
     // This fails because witness and witness2 have different root hashes, and constructing
@@ -38,7 +55,7 @@ fn test_transaction() {
     //     .expect("should be able to prove second spend");
 
     let public_transaction = transaction
-        .post(None, 1)
+        .post(&spender_key, None, 1)
         .expect("should be able to post transaction");
     public_transaction
         .verify()
@@ -76,10 +93,10 @@ fn test_transaction() {
 fn test_miners_fee() {
     let receiver_key: SaplingKey = SaplingKey::generate_key();
     let out_note = Note::new(receiver_key.generate_public_address(), 42, "");
-    let mut transaction = ProposedTransaction::new(receiver_key);
-    transaction.add_output(out_note);
+    let mut transaction = ProposedTransaction::new();
+    transaction.add_output(out_note).unwrap();
     let posted_transaction = transaction
-        .post_miners_fee()
+        .post_miners_fee(&receiver_key)
         .expect("it is a valid miner's fee");
     assert_eq!(posted_transaction.fee, -42);
     assert_eq!(
@@ -100,19 +117,19 @@ fn test_transaction_signature() {
     let spender_address = spender_key.generate_public_address();
     let receiver_address = receiver_key.generate_public_address();
 
-    let mut transaction = ProposedTransaction::new(spender_key);
+    let mut transaction = ProposedTransaction::new();
     let in_note = Note::new(spender_address, 42, "");
     let out_note = Note::new(receiver_address, 41, "");
     let witness = make_fake_witness(&in_note);
 
-    transaction.add_spend(in_note, &witness);
+    transaction.add_spend(in_note, &witness).unwrap();
 
-    transaction.add_output(out_note);
+    transaction.add_output(out_note).unwrap();
 
     transaction.set_expiration_sequence(1337);
 
     let public_transaction = transaction
-        .post(None, 0)
+        .post(&spender_key, None, 0)
         .expect("should be able to post transaction");
 
     let mut serialized_signature = vec![];
@@ -124,3 +141,1223 @@ fn test_transaction_signature() {
     Signature::read(&mut serialized_signature[..].as_ref())
         .expect("Can deserialize back into a valid Signature");
 }
+
+#[test]
+fn test_transaction_mint() {
+    let key = SaplingKey::generate_key();
+    let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+        .expect("can create an asset");
+
+    // Notes only hold the native asset, so minted value can't be returned as
+    // change
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_mint(asset, 5).unwrap();
+    transaction.add_burn(*asset.identifier(), 2).unwrap();
+    assert!(matches!(
+        transaction.post(&key, None, 0),
+        Err(IronfishError::InvalidBalance)
+    ));
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_mint(asset, 5).unwrap();
+    transaction.add_burn(*asset.identifier(), 5).unwrap();
+
+    let public_transaction = transaction
+        .post(&key, None, 0)
+        .expect("should be able to post transaction");
+    public_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+
+    assert_eq!(public_transaction.mints.len(), 1);
+    assert_eq!(public_transaction.mints[0].value(), 5);
+    assert_eq!(
+        public_transaction.mints[0].asset_identifier(),
+        asset.identifier()
+    );
+    assert_eq!(public_transaction.burns.len(), 1);
+    assert!(public_transaction.outputs.is_empty());
+
+    // test serialization
+    let mut serialized_transaction = vec![];
+    public_transaction
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    let read_back_transaction = Transaction::read(&serialized_transaction[..])
+        .expect("should be able to deserialize valid transaction");
+    assert_eq!(read_back_transaction.mints.len(), 1);
+    read_back_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+}
+
+#[test]
+fn test_transaction_burn() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let spender_address = spender_key.generate_public_address();
+    let receiver_address = receiver_key.generate_public_address();
+
+    let asset = Asset::new(
+        spender_key.asset_public_key(),
+        "name",
+        "chain",
+        "network",
+        "token",
+    )
+    .expect("can create an asset");
+    let in_note = Note::new(spender_address, 10, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction
+        .add_output(Note::new(receiver_address, 9, ""))
+        .unwrap();
+    transaction.add_mint(asset, 4).unwrap();
+    transaction.add_burn(*asset.identifier(), 4).unwrap();
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    public_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+
+    assert_eq!(public_transaction.burns().len(), 1);
+    assert_eq!(
+        public_transaction.burns()[0].asset_identifier(),
+        asset.identifier()
+    );
+    assert_eq!(public_transaction.burns()[0].value(), 4);
+    assert_eq!(public_transaction.outputs.len(), 1);
+
+    // test serialization
+    let mut serialized_transaction = vec![];
+    public_transaction
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    let read_back_transaction = Transaction::read(&serialized_transaction[..])
+        .expect("should be able to deserialize valid transaction");
+    assert_eq!(read_back_transaction.burns.len(), 1);
+    read_back_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+}
+
+#[test]
+fn test_minted_value_only_balances_its_own_asset() {
+    let key = SaplingKey::generate_key();
+    let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+        .expect("can create an asset");
+
+    // Minting 5 of an asset can't pay for 5 of the native asset
+    let mut value_balances = ValueBalances::new();
+    value_balances.add(asset.identifier(), 5).unwrap();
+    value_balances.subtract(&NATIVE_ASSET, 5).unwrap();
+    assert_ne!(
+        value_balance_to_point(&value_balances).expect("should have a point"),
+        ExtendedPoint::identity()
+    );
+
+    let mut value_balances = ValueBalances::new();
+    value_balances.add(asset.identifier(), 5).unwrap();
+    value_balances.subtract(asset.identifier(), 5).unwrap();
+    assert_eq!(
+        value_balance_to_point(&value_balances).expect("should have a point"),
+        ExtendedPoint::identity()
+    );
+}
+
+#[test]
+fn test_transaction_burn_more_than_spent() {
+    let spender_key = SaplingKey::generate_key();
+    let spender_address = spender_key.generate_public_address();
+
+    let asset = Asset::new(
+        spender_key.asset_public_key(),
+        "name",
+        "chain",
+        "network",
+        "token",
+    )
+    .expect("can create an asset");
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_mint(asset, 10).unwrap();
+    transaction.add_burn(*asset.identifier(), 11).unwrap();
+
+    assert!(transaction.post(&spender_key, None, 0).is_err());
+}
+
+#[test]
+fn test_transaction_detached_signing() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let out_note = Note::new(receiver_key.generate_public_address(), 40, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction.add_output(out_note).unwrap();
+
+    // The proofs are created without the spend authorizing key
+    let unsigned_transaction = transaction
+        .build(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            None,
+            spender_key.generate_public_address(),
+            1,
+        )
+        .expect("should be able to build transaction");
+    assert_eq!(unsigned_transaction.fee(), 1);
+    assert_eq!(unsigned_transaction.outputs().len(), 2);
+    let signature_hash = unsigned_transaction.transaction_signature_hash();
+
+    let public_transaction = unsigned_transaction
+        .sign(&spender_key)
+        .expect("should be able to sign transaction");
+    public_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+    assert_eq!(
+        public_transaction.transaction_signature_hash(),
+        signature_hash
+    );
+}
+
+#[test]
+fn test_transaction_detached_signing_wrong_key() {
+    let spender_key = SaplingKey::generate_key();
+    let other_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
+
+    let unsigned_transaction = transaction
+        .build(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            None,
+            spender_key.generate_public_address(),
+            0,
+        )
+        .expect("should be able to build transaction");
+
+    assert!(unsigned_transaction.sign(&other_key).is_err());
+}
+
+#[test]
+fn test_transaction_build_mint_without_asset_key() {
+    let key = SaplingKey::generate_key();
+    let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+        .expect("can create an asset");
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_mint(asset, 5).unwrap();
+    transaction.add_burn(*asset.identifier(), 5).unwrap();
+
+    assert!(matches!(
+        transaction.build(
+            &key.sapling_proof_generation_key(),
+            key.outgoing_view_key(),
+            None,
+            key.generate_public_address(),
+            0,
+        ),
+        Err(IronfishError::InvalidSigningKey)
+    ));
+}
+
+#[test]
+fn test_proposed_transaction_round_trip() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let asset = Asset::new(
+        spender_key.asset_public_key(),
+        "name",
+        "chain",
+        "network",
+        "token",
+    )
+    .expect("can create an asset");
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let out_note = Note::new(receiver_key.generate_public_address(), 40, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction.add_output(out_note).unwrap();
+    transaction.add_mint(asset, 5).unwrap();
+    transaction.add_burn(*asset.identifier(), 5).unwrap();
+    transaction.set_expiration_sequence(10);
+
+    // A watch-only wallet hands the proposed transaction to a prover
+    let mut serialized_proposed = vec![];
+    transaction
+        .write(&mut serialized_proposed)
+        .expect("should be able to serialize proposed transaction");
+    let mut read_back_proposed = ProposedTransaction::read(&serialized_proposed[..])
+        .expect("should be able to deserialize proposed transaction");
+    assert_eq!(read_back_proposed.spends.len(), 1);
+    assert_eq!(read_back_proposed.outputs.len(), 1);
+    assert_eq!(read_back_proposed.mints.len(), 1);
+    assert_eq!(read_back_proposed.burns.len(), 1);
+    assert_eq!(read_back_proposed.expiration_sequence(), Some(10));
+
+    let mut reserialized_proposed = vec![];
+    read_back_proposed
+        .write(&mut reserialized_proposed)
+        .expect("should be able to serialize proposed transaction again");
+    assert_eq!(serialized_proposed, reserialized_proposed);
+
+    // The prover hands the unsigned transaction to the signer
+    let unsigned_transaction = read_back_proposed
+        .build(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            Some(spender_key.asset_authorization_key()),
+            spender_key.generate_public_address(),
+            1,
+        )
+        .expect("should be able to build transaction");
+
+    let mut serialized_unsigned = vec![];
+    unsigned_transaction
+        .write(&mut serialized_unsigned)
+        .expect("should be able to serialize unsigned transaction");
+    let read_back_unsigned = UnsignedTransaction::read(&serialized_unsigned[..])
+        .expect("should be able to deserialize unsigned transaction");
+    assert_eq!(read_back_unsigned.fee(), 1);
+    assert_eq!(read_back_unsigned.expiration_sequence(), 10);
+    assert_eq!(
+        read_back_unsigned.transaction_signature_hash(),
+        unsigned_transaction.transaction_signature_hash()
+    );
+
+    let public_transaction = read_back_unsigned
+        .sign(&spender_key)
+        .expect("should be able to sign transaction");
+    public_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+}
+
+#[test]
+fn test_proposed_transaction_unsupported_version() {
+    let mut serialized_proposed = vec![];
+    ProposedTransaction::new()
+        .write(&mut serialized_proposed)
+        .expect("should be able to serialize proposed transaction");
+    serialized_proposed[0] = 0;
+
+    assert!(ProposedTransaction::read(&serialized_proposed[..]).is_err());
+}
+
+#[test]
+fn test_transaction_parallel_proving() {
+    configure_proving_threads(4).expect("should be able to create proving thread pool");
+
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    for _ in 0..3 {
+        let in_note = Note::new(spender_key.generate_public_address(), 10, "");
+        let witness = make_fake_witness(&in_note);
+        transaction.add_spend(in_note, &witness).unwrap();
+    }
+    for _ in 0..3 {
+        let out_note = Note::new(receiver_key.generate_public_address(), 9, "");
+        transaction.add_output(out_note).unwrap();
+    }
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    public_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+
+    assert_eq!(public_transaction.spends.len(), 3);
+    // Three outputs and the change
+    assert_eq!(public_transaction.outputs.len(), 4);
+
+    // Descriptions are kept in the order they were added
+    for (output, builder) in public_transaction
+        .outputs
+        .iter()
+        .zip(transaction.outputs.iter())
+    {
+        assert_eq!(
+            output.merkle_note.note_commitment,
+            builder.note.commitment_point()
+        );
+    }
+}
+
+fn make_simple_transaction() -> Transaction {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let out_note = Note::new(receiver_key.generate_public_address(), 40, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction.add_output(out_note).unwrap();
+
+    transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction")
+}
+
+#[test]
+fn test_batch_verify_with_results() {
+    let valid_transaction = make_simple_transaction();
+    let other_valid_transaction = make_simple_transaction();
+    let mut invalid_transaction = make_simple_transaction();
+    invalid_transaction.fee += 1;
+
+    let transactions = [
+        valid_transaction,
+        invalid_transaction,
+        other_valid_transaction,
+    ];
+    assert!(batch_verify_transactions(transactions.iter()).is_err());
+
+    let results = batch_verify_transactions_with_results(transactions.iter());
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+    assert!(results[2].is_ok());
+}
+
+#[test]
+fn test_batch_verify_with_results_duplicate_nullifier_invalid_proof() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_address = SaplingKey::generate_key().generate_public_address();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut honest_transaction = ProposedTransaction::new();
+    honest_transaction.set_expiration_sequence(0);
+    honest_transaction
+        .add_spend(in_note.clone(), &witness)
+        .unwrap();
+    honest_transaction
+        .add_output(Note::new(receiver_address, 40, ""))
+        .unwrap();
+    let honest_transaction = honest_transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+
+    // Spends the same note, with a proof that doesn't match the spend but
+    // with every signature valid
+    let mut bogus_transaction = ProposedTransaction::new();
+    bogus_transaction.set_expiration_sequence(0);
+    bogus_transaction.add_spend(in_note, &witness).unwrap();
+    bogus_transaction
+        .add_output(Note::new(receiver_address, 40, ""))
+        .unwrap();
+    let mut unsigned_transaction = bogus_transaction
+        .build(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            None,
+            spender_key.generate_public_address(),
+            1,
+        )
+        .expect("should be able to build transaction");
+    unsigned_transaction.spends[0].spend_proof.proof = honest_transaction.spends[0].proof.clone();
+    let (private_key, public_key) = bogus_transaction
+        .binding_signature_keys()
+        .expect("should be able to create binding signature keys");
+    unsigned_transaction.binding_signature = bogus_transaction
+        .binding_signature(
+            &private_key,
+            &public_key,
+            &unsigned_transaction.transaction_signature_hash(),
+            &mut StdRng::seed_from_u64(0),
+        )
+        .expect("should be able to sign transaction");
+    let bogus_transaction = unsigned_transaction
+        .sign(&spender_key)
+        .expect("should be able to sign transaction");
+    assert_eq!(
+        bogus_transaction.spends[0].nullifier,
+        honest_transaction.spends[0].nullifier
+    );
+
+    // The bogus transaction comes first, but only fails its own proof
+    let transactions = [bogus_transaction, honest_transaction];
+    let results = batch_verify_transactions_with_results(transactions.iter());
+    assert!(matches!(
+        results[0],
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::SpendProof(0)
+        ))
+    ));
+    assert!(results[1].is_ok());
+
+    // Verifying the batch as a whole fails with the same check
+    assert!(matches!(
+        batch_verify_transactions(transactions.iter()),
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::SpendProof(0)
+        ))
+    ));
+}
+
+#[test]
+fn test_bisect_proofs() {
+    let transactions: Vec<Transaction> = (0..5).map(|_| make_simple_transaction()).collect();
+
+    // Swap in a proof that is valid, but for different inputs
+    let mut invalid_transaction = transactions[3].clone();
+    invalid_transaction.spends[0].proof = transactions[0].spends[0].proof.clone();
+
+    let candidates = vec![
+        (0, &transactions[0]),
+        (1, &transactions[1]),
+        (2, &transactions[2]),
+        (3, &invalid_transaction),
+        (4, &transactions[4]),
+    ];
+    let mut results: Vec<_> = candidates.iter().map(|_| Ok(())).collect();
+
+    bisect_proofs(&candidates, &mut results);
+
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(results[2].is_ok());
+    assert!(matches!(
+        results[3],
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::SpendProof(0)
+        ))
+    ));
+    assert!(results[4].is_ok());
+}
+
+#[test]
+fn test_verification_error_names_check() {
+    let transaction = make_simple_transaction();
+
+    let mut wrong_fee = transaction.clone();
+    wrong_fee.fee += 1;
+    assert!(matches!(
+        wrong_fee.verify(),
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::BindingSignature
+        ))
+    ));
+
+    let mut unsigned_spend = transaction.clone();
+    unsigned_spend.spends[0].authorizing_signature = Signature::read(&[0u8; 64][..]).unwrap();
+    assert!(matches!(
+        unsigned_spend.verify(),
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::SpendSignature(0)
+        ))
+    ));
+}
+
+#[test]
+fn test_read_with_limits() {
+    let transaction = make_simple_transaction();
+    let mut serialized_transaction = vec![];
+    transaction
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+
+    let limits = TransactionLimits::default();
+    let read_back_transaction = Transaction::read_with_limits(&serialized_transaction, &limits)
+        .expect("should be able to deserialize valid transaction");
+    read_back_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+
+    // Extra bytes after the transaction
+    let mut trailing = serialized_transaction.clone();
+    trailing.push(0);
+    assert!(matches!(
+        Transaction::read_with_limits(&trailing, &limits),
+        Err(IronfishError::TrailingBytes)
+    ));
+
+    // Bigger than the maximum size
+    let small_limits = TransactionLimits {
+        max_size: serialized_transaction.len() - 1,
+        ..TransactionLimits::default()
+    };
+    assert!(matches!(
+        Transaction::read_with_limits(&serialized_transaction, &small_limits),
+        Err(IronfishError::TransactionTooLarge)
+    ));
+
+    // More outputs than allowed
+    let output_limits = TransactionLimits {
+        max_outputs: 1,
+        ..TransactionLimits::default()
+    };
+    assert!(matches!(
+        Transaction::read_with_limits(&serialized_transaction, &output_limits),
+        Err(IronfishError::TransactionTooLarge)
+    ));
+}
+
+#[test]
+fn test_read_with_limits_huge_counts() {
+    // Claims u64::MAX of every description, which must be rejected before
+    // anything is allocated
+    let mut payload = (TRANSACTION_VERSION_FLAG | TransactionVersion::V2 as u32)
+        .to_le_bytes()
+        .to_vec();
+    payload.extend_from_slice(&[0xff; 32]);
+    payload.extend_from_slice(&[0; 12]);
+
+    assert!(matches!(
+        Transaction::read_with_limits(&payload, &TransactionLimits::default()),
+        Err(IronfishError::TransactionTooLarge)
+    ));
+
+    // Claims as many spends as the limits allow, in far fewer bytes than
+    // that many spends take up
+    let mut payload = (TRANSACTION_VERSION_FLAG | TransactionVersion::V2 as u32)
+        .to_le_bytes()
+        .to_vec();
+    payload.extend_from_slice(&TransactionLimits::default().max_spends.to_le_bytes());
+    payload.extend_from_slice(&[0; 24 + 12 + 64]);
+
+    assert!(matches!(
+        Transaction::read_with_limits(&payload, &TransactionLimits::default()),
+        Err(IronfishError::TransactionTooLarge)
+    ));
+
+    // A valid transaction cut short claims more than is left
+    let mut serialized_transaction = vec![];
+    make_simple_transaction()
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    serialized_transaction.pop();
+
+    assert!(matches!(
+        Transaction::read_with_limits(&serialized_transaction, &TransactionLimits::default()),
+        Err(IronfishError::TransactionTooLarge)
+    ));
+    assert!(Transaction::read(&serialized_transaction[..]).is_err());
+}
+
+#[test]
+fn test_transaction_versions() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+
+    for version in [TransactionVersion::V1, TransactionVersion::V2] {
+        let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+        let out_note = Note::new(receiver_key.generate_public_address(), 40, "");
+        let witness = make_fake_witness(&in_note);
+
+        let mut transaction = ProposedTransaction::new();
+        transaction.set_expiration_sequence(0);
+        transaction.set_version(version);
+        transaction.add_spend(in_note, &witness).unwrap();
+        transaction.add_output(out_note).unwrap();
+
+        let public_transaction = transaction
+            .post(&spender_key, None, 1)
+            .expect("should be able to post transaction");
+        assert_eq!(public_transaction.version(), version);
+
+        let mut serialized_transaction = vec![];
+        public_transaction
+            .write(&mut serialized_transaction)
+            .expect("should be able to serialize transaction");
+
+        let read_back_transaction = Transaction::read(&serialized_transaction[..])
+            .expect("should be able to deserialize valid transaction");
+        assert_eq!(read_back_transaction.version(), version);
+        read_back_transaction
+            .verify()
+            .expect("Should be able to verify transaction");
+
+        let mut reserialized_transaction = vec![];
+        read_back_transaction
+            .write(&mut reserialized_transaction)
+            .expect("should be able to serialize transaction again");
+        assert_eq!(serialized_transaction, reserialized_transaction);
+    }
+}
+
+#[test]
+fn test_transaction_v1_layout() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let out_note = Note::new(receiver_key.generate_public_address(), 40, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut proposed_transaction = ProposedTransaction::new();
+    proposed_transaction.set_expiration_sequence(1337);
+    proposed_transaction.set_version(TransactionVersion::V1);
+    proposed_transaction.add_spend(in_note, &witness).unwrap();
+    proposed_transaction.add_output(out_note).unwrap();
+    let transaction = proposed_transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+
+    // The layout written by transactions from before versioning: no header,
+    // no mint or burn counts
+    let mut baseline_transaction = vec![];
+    baseline_transaction
+        .write_u64::<LittleEndian>(transaction.spends().len() as u64)
+        .unwrap();
+    baseline_transaction
+        .write_u64::<LittleEndian>(transaction.outputs().len() as u64)
+        .unwrap();
+    baseline_transaction
+        .write_i64::<LittleEndian>(transaction.fee())
+        .unwrap();
+    baseline_transaction
+        .write_u32::<LittleEndian>(transaction.expiration_sequence())
+        .unwrap();
+    for spend in transaction.iter_spends() {
+        spend.write(&mut baseline_transaction).unwrap();
+    }
+    for output in transaction.iter_outputs() {
+        output.write(&mut baseline_transaction).unwrap();
+    }
+    transaction
+        .binding_signature()
+        .write(&mut baseline_transaction)
+        .unwrap();
+
+    let mut serialized_transaction = vec![];
+    transaction
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    assert_eq!(serialized_transaction, baseline_transaction);
+
+    let read_back_transaction = Transaction::read(&baseline_transaction[..])
+        .expect("should be able to deserialize baseline transaction");
+    assert_eq!(read_back_transaction.version(), TransactionVersion::V1);
+    assert_eq!(read_back_transaction.expiration_sequence(), 1337);
+    read_back_transaction
+        .verify()
+        .expect("Should be able to verify transaction");
+    let read_back_transaction =
+        Transaction::read_with_limits(&baseline_transaction, &TransactionLimits::default())
+            .expect("should be able to deserialize baseline transaction with limits");
+    assert_eq!(read_back_transaction.id(), transaction.id());
+
+    // Mints and burns don't fit in the V1 layout
+    let asset = Asset::new(
+        spender_key.asset_public_key(),
+        "Testcoin",
+        "chain",
+        "network",
+        "token",
+    )
+    .expect("should be able to create an asset");
+    let mut proposed_transaction = ProposedTransaction::new();
+    proposed_transaction.set_expiration_sequence(0);
+    proposed_transaction.set_version(TransactionVersion::V1);
+    proposed_transaction.add_mint(asset, 5).unwrap();
+    assert!(matches!(
+        proposed_transaction.post(&spender_key, None, 0),
+        Err(IronfishError::UnsupportedVersion)
+    ));
+
+    // A version that doesn't exist is rejected
+    let mut unknown_version = (TRANSACTION_VERSION_FLAG | 9).to_le_bytes().to_vec();
+    unknown_version.extend_from_slice(&serialized_transaction);
+    assert!(matches!(
+        Transaction::read(&unknown_version[..]),
+        Err(IronfishError::UnsupportedVersion)
+    ));
+}
+
+#[test]
+fn test_transaction_id() {
+    let transaction = make_simple_transaction();
+
+    let mut serialized_transaction = vec![];
+    transaction
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    let read_back_transaction = Transaction::read(&serialized_transaction[..])
+        .expect("should be able to deserialize valid transaction");
+    assert_eq!(transaction.id(), read_back_transaction.id());
+    assert_eq!(
+        transaction.unsigned_id(),
+        read_back_transaction.unsigned_id()
+    );
+
+    assert_ne!(transaction.id(), transaction.unsigned_id());
+    assert_ne!(transaction.id(), transaction.transaction_signature_hash());
+    assert_ne!(
+        transaction.unsigned_id(),
+        transaction.transaction_signature_hash()
+    );
+
+    // Replacing a signature changes the id, but not the unsigned id
+    let mut resigned_transaction = transaction.clone();
+    resigned_transaction.binding_signature = Signature::read(&[0u8; 64][..]).unwrap();
+    assert_ne!(transaction.id(), resigned_transaction.id());
+    assert_eq!(
+        transaction.unsigned_id(),
+        resigned_transaction.unsigned_id()
+    );
+}
+
+struct TestChainContext {
+    anchors: Vec<(Scalar, u32)>,
+    spent_nullifiers: Vec<Nullifier>,
+    sequence: u32,
+}
+
+impl ChainContext for TestChainContext {
+    fn is_known_anchor(&self, root_hash: &Scalar, tree_size: u32) -> bool {
+        self.anchors.contains(&(*root_hash, tree_size))
+    }
+
+    fn is_nullifier_spent(&self, nullifier: &Nullifier) -> bool {
+        self.spent_nullifiers.contains(nullifier)
+    }
+
+    fn current_sequence(&self) -> u32 {
+        self.sequence
+    }
+}
+
+#[test]
+fn test_verify_contextual() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let out_note = Note::new(receiver_key.generate_public_address(), 40, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction.add_output(out_note).unwrap();
+    transaction.set_expiration_sequence(10);
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    let spend = &public_transaction.spends()[0];
+
+    let valid_context = TestChainContext {
+        anchors: vec![(spend.root_hash(), spend.tree_size())],
+        spent_nullifiers: vec![],
+        sequence: 9,
+    };
+    public_transaction
+        .verify_contextual(&valid_context)
+        .expect("Should be able to verify transaction");
+
+    let expired_context = TestChainContext {
+        sequence: 10,
+        ..valid_context
+    };
+    assert!(matches!(
+        public_transaction.verify_contextual(&expired_context),
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::Expired
+        ))
+    ));
+
+    let unknown_anchor_context = TestChainContext {
+        anchors: vec![(spend.root_hash(), spend.tree_size() + 1)],
+        spent_nullifiers: vec![],
+        sequence: 9,
+    };
+    assert!(matches!(
+        public_transaction.verify_contextual(&unknown_anchor_context),
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::SpendAnchor(0)
+        ))
+    ));
+
+    let double_spend_context = TestChainContext {
+        anchors: vec![(spend.root_hash(), spend.tree_size())],
+        spent_nullifiers: vec![spend.nullifier()],
+        sequence: 9,
+    };
+    assert!(matches!(
+        public_transaction.verify_contextual(&double_spend_context),
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::SpendNullifier(0)
+        ))
+    ));
+}
+
+#[test]
+fn test_duplicate_nullifier_in_transaction() {
+    let mut transaction = make_simple_transaction();
+    let spend = transaction.spends[0].clone();
+    transaction.spends.push(spend);
+
+    assert!(matches!(
+        transaction.verify(),
+        Err(IronfishError::DuplicateNullifier(DuplicateNullifier {
+            first: (0, 0),
+            second: (0, 1),
+        }))
+    ));
+}
+
+#[test]
+fn test_duplicate_nullifier_in_batch() {
+    let transaction = make_simple_transaction();
+    let other_transaction = make_simple_transaction();
+    let transactions = [transaction.clone(), other_transaction, transaction];
+
+    assert!(matches!(
+        check_duplicate_nullifiers(transactions.iter()),
+        Err(IronfishError::DuplicateNullifier(DuplicateNullifier {
+            first: (0, 0),
+            second: (2, 0),
+        }))
+    ));
+
+    // Both batch paths fail on the same spends
+    assert!(matches!(
+        batch_verify_transactions(transactions.iter()),
+        Err(IronfishError::DuplicateNullifier(DuplicateNullifier {
+            first: (0, 0),
+            second: (2, 0),
+        }))
+    ));
+
+    let results = batch_verify_transactions_with_results(transactions.iter());
+    assert!(results[0].is_ok());
+    assert!(results[1].is_ok());
+    assert!(matches!(
+        results[2],
+        Err(IronfishError::DuplicateNullifier(DuplicateNullifier {
+            first: (0, 0),
+            second: (2, 0),
+        }))
+    ));
+}
+
+#[test]
+fn test_transaction_expiration() {
+    assert!(!is_expired_sequence(0, 0));
+    assert!(!is_expired_sequence(0, u32::MAX));
+    assert!(!is_expired_sequence(10, 9));
+    assert!(is_expired_sequence(10, 10));
+    assert!(is_expired_sequence(10, 11));
+
+    assert_eq!(
+        default_expiration_sequence(100),
+        100 + DEFAULT_EXPIRATION_SEQUENCE_DELTA
+    );
+    assert_eq!(default_expiration_sequence(u32::MAX), u32::MAX);
+    assert!(!is_expired_sequence(default_expiration_sequence(100), 100));
+
+    let mut proposed_transaction = ProposedTransaction::new();
+    assert_eq!(proposed_transaction.expiration_sequence(), None);
+    assert!(!proposed_transaction.is_expired(1000));
+    proposed_transaction.set_default_expiration_sequence(100);
+    assert_eq!(
+        proposed_transaction.expiration_sequence(),
+        Some(100 + DEFAULT_EXPIRATION_SEQUENCE_DELTA)
+    );
+    assert!(!proposed_transaction.is_expired(100));
+    assert!(proposed_transaction.is_expired(100 + DEFAULT_EXPIRATION_SEQUENCE_DELTA));
+
+    // Without an expiration, the transaction is posted to never expire
+    let spender_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let witness = make_fake_witness(&in_note);
+    let mut proposed_transaction = ProposedTransaction::new();
+    proposed_transaction
+        .add_spend(in_note.clone(), &witness)
+        .unwrap();
+    let posted_transaction = proposed_transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    assert_eq!(posted_transaction.expiration_sequence(), 0);
+    assert!(!posted_transaction.is_expired(u32::MAX));
+
+    let mut proposed_transaction = ProposedTransaction::new();
+    proposed_transaction.add_spend(in_note, &witness).unwrap();
+    proposed_transaction.set_default_expiration_sequence(100);
+    let posted_transaction = proposed_transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    assert_eq!(
+        posted_transaction.expiration_sequence(),
+        100 + DEFAULT_EXPIRATION_SEQUENCE_DELTA
+    );
+
+    let mut transaction = make_simple_transaction();
+    assert!(!transaction.is_expired(1000));
+    transaction.expiration_sequence = 5;
+    assert!(!transaction.is_expired(4));
+    assert!(transaction.is_expired(5));
+}
+
+#[test]
+fn test_transaction_deterministic_with_rng() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_address = SaplingKey::generate_key().generate_public_address();
+    let change_address = spender_key.generate_public_address();
+    let asset = Asset::new(
+        spender_key.asset_public_key(),
+        "Testcoin",
+        "chain",
+        "network",
+        "token",
+    )
+    .expect("should be able to create an asset");
+
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let witness = make_fake_witness(&in_note);
+
+    let post_with_seed = |seed: u64| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let out_note = Note::new_with_rng(receiver_address, 40, "", &mut rng);
+
+        let mut transaction = ProposedTransaction::new();
+        transaction.set_expiration_sequence(0);
+        transaction
+            .add_spend_with_rng(in_note.clone(), &witness, &mut rng)
+            .unwrap();
+        transaction.add_output_with_rng(out_note, &mut rng).unwrap();
+        transaction.add_mint(asset, 5).unwrap();
+        transaction.add_burn(*asset.identifier(), 5).unwrap();
+
+        let posted_transaction = transaction
+            .post_with_rng(&spender_key, Some(change_address), 1, &mut rng)
+            .expect("should be able to post transaction");
+        posted_transaction
+            .verify()
+            .expect("should be able to verify transaction");
+
+        let mut serialized_transaction = vec![];
+        posted_transaction
+            .write(&mut serialized_transaction)
+            .expect("should be able to serialize transaction");
+        serialized_transaction
+    };
+
+    assert_eq!(post_with_seed(1), post_with_seed(1));
+    assert_ne!(post_with_seed(1), post_with_seed(2));
+}
+
+#[test]
+fn test_transaction_estimated_size() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let asset = Asset::new(
+        spender_key.asset_public_key(),
+        "Testcoin",
+        "chain",
+        "network",
+        "token",
+    )
+    .expect("should be able to create an asset");
+
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let out_note = Note::new(receiver_key.generate_public_address(), 40, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction.add_output(out_note).unwrap();
+    transaction.add_mint(asset, 5).unwrap();
+    transaction.add_burn(*asset.identifier(), 5).unwrap();
+
+    // Change is returned in the native asset
+    let estimated_size = transaction.estimated_size();
+    assert_eq!(estimated_size, 4 + 44 + 388 + 2 * 467 + 328 + 40 + 64);
+
+    // V1 has no header and no mint or burn counts
+    transaction.set_version(TransactionVersion::V1);
+    assert_eq!(transaction.estimated_size(), estimated_size - 4 - 16);
+    transaction.set_version(TransactionVersion::V2);
+
+    assert_eq!(transaction.estimate_fee(0), 1);
+    assert_eq!(transaction.estimate_fee(1000), estimated_size as u64);
+    assert_eq!(transaction.estimate_fee(10), estimated_size as u64 / 100);
+
+    let posted_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+
+    let mut serialized_transaction = vec![];
+    posted_transaction
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    assert_eq!(serialized_transaction.len(), estimated_size);
+}
+
+#[test]
+fn test_native_burn_cannot_create_coins() {
+    let spender_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
+
+    assert!(matches!(
+        transaction.add_burn(NATIVE_ASSET, 1),
+        Err(IronfishError::InvalidAssetIdentifier)
+    ));
+    assert!(matches!(
+        transaction.add_burn([9u8; 32], u64::MAX),
+        Err(IronfishError::IllegalValue)
+    ));
+
+    let mut unsigned_transaction = transaction
+        .build(
+            &spender_key.sapling_proof_generation_key(),
+            spender_key.outgoing_view_key(),
+            None,
+            spender_key.generate_public_address(),
+            42,
+        )
+        .expect("should be able to build transaction");
+
+    // Burning 2^64 - X of the native asset used to be counted as -X, which
+    // let the fee claim X more than was spent with a valid binding signature
+    let extra = 10;
+    unsigned_transaction.fee += extra as i64;
+    unsigned_transaction.burns.push(BurnDescription {
+        asset_identifier: NATIVE_ASSET,
+        value: 0u64.wrapping_sub(extra),
+    });
+
+    let (private_key, public_key) = transaction.binding_signature_keys().unwrap();
+    let signature_hash = unsigned_transaction.transaction_signature_hash();
+    unsigned_transaction.binding_signature = transaction
+        .binding_signature(
+            &private_key,
+            &public_key,
+            &signature_hash,
+            &mut rand::thread_rng(),
+        )
+        .unwrap();
+    let forged_transaction = unsigned_transaction.sign(&spender_key).unwrap();
+
+    assert!(matches!(
+        forged_transaction.value_balances(),
+        Err(IronfishError::IllegalValue)
+    ));
+    assert!(matches!(
+        forged_transaction.verify(),
+        Err(IronfishError::InvalidTransaction(
+            VerificationCheck::BurnValue(0)
+        ))
+    ));
+
+    let mut serialized_transaction = vec![];
+    forged_transaction
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    assert!(matches!(
+        Transaction::read(&serialized_transaction[..]),
+        Err(IronfishError::InvalidAssetIdentifier)
+    ));
+}
+
+#[test]
+fn test_value_balance_overflow_is_an_error() {
+    let spender_key = SaplingKey::generate_key();
+    let in_note = Note::new(spender_key.generate_public_address(), 42, "");
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    transaction.add_spend(in_note, &witness).unwrap();
+    let valid_transaction = transaction
+        .post(&spender_key, None, 42)
+        .expect("should be able to post transaction");
+
+    // Two burns of the same asset that overflow its balance
+    let mut overflowing_burns = valid_transaction.clone();
+    for _ in 0..2 {
+        overflowing_burns.burns.push(BurnDescription {
+            asset_identifier: [9u8; 32],
+            value: i64::MAX as u64,
+        });
+    }
+    assert!(matches!(
+        overflowing_burns.value_balances(),
+        Err(IronfishError::IllegalValue)
+    ));
+    assert!(overflowing_burns.verify().is_err());
+
+    // A fee and a native burn that overflow the native balance
+    let mut overflowing_fee = valid_transaction;
+    overflowing_fee.fee = i64::MAX;
+    overflowing_fee.burns.push(BurnDescription {
+        asset_identifier: NATIVE_ASSET,
+        value: 1,
+    });
+    assert!(matches!(
+        overflowing_fee.value_balances(),
+        Err(IronfishError::IllegalValue)
+    ));
+    assert!(overflowing_fee.verify().is_err());
+}
+
+#[test]
+fn test_mint_value_above_i64_max_is_rejected() {
+    let key = SaplingKey::generate_key();
+    let asset = Asset::new(key.asset_public_key(), "name", "chain", "network", "token")
+        .expect("can create an asset");
+
+    let mut transaction = ProposedTransaction::new();
+    transaction.set_expiration_sequence(0);
+    assert!(matches!(
+        transaction.add_mint(asset, u64::MAX),
+        Err(IronfishError::IllegalValue)
+    ));
+    assert!(transaction.mints.is_empty());
+
+    transaction.add_mint(asset, 5).unwrap();
+    transaction.add_burn(*asset.identifier(), 5).unwrap();
+    let public_transaction = transaction
+        .post(&key, None, 0)
+        .expect("should be able to post transaction");
+
+    // A mint of 2^64 - 1 used to be counted as a mint of -1, a hidden burn
+    let mut hidden_burn = public_transaction;
+    hidden_burn.mints[0].value = u64::MAX;
+    assert!(matches!(
+        hidden_burn.value_balances(),
+        Err(IronfishError::IllegalValue)
+    ));
+    assert!(hidden_burn.verify().is_err());
+
+    let mut serialized_transaction = vec![];
+    hidden_burn
+        .write(&mut serialized_transaction)
+        .expect("should be able to serialize transaction");
+    assert!(matches!(
+        Transaction::read(&serialized_transaction[..]),
+        Err(IronfishError::IllegalValue)
+    ));
+}
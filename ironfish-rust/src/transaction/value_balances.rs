@@ -3,8 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 use std::collections::{hash_map, HashMap};
 
-use crate::assets::asset::{AssetIdentifier, NATIVE_ASSET};
+use crate::{
+    assets::asset::{AssetIdentifier, NATIVE_ASSET},
+    errors::IronfishError,
+};
 
+#[derive(Clone)]
 pub struct ValueBalances {
     values: HashMap<AssetIdentifier, i64>,
 }
@@ -18,14 +22,34 @@ impl ValueBalances {
         ValueBalances { values: hash_map }
     }
 
-    pub fn add(&mut self, asset_identifier: &AssetIdentifier, value: i64) {
+    /// Fails with [`IronfishError::IllegalValue`] if the balance would
+    /// overflow.
+    pub fn add(
+        &mut self,
+        asset_identifier: &AssetIdentifier,
+        value: i64,
+    ) -> Result<(), IronfishError> {
         let current_value = self.values.entry(*asset_identifier).or_insert(0);
-        *current_value += value
+        *current_value = current_value
+            .checked_add(value)
+            .ok_or(IronfishError::IllegalValue)?;
+
+        Ok(())
     }
 
-    pub fn subtract(&mut self, asset_identifier: &AssetIdentifier, value: i64) {
+    /// Fails with [`IronfishError::IllegalValue`] if the balance would
+    /// overflow.
+    pub fn subtract(
+        &mut self,
+        asset_identifier: &AssetIdentifier,
+        value: i64,
+    ) -> Result<(), IronfishError> {
         let current_value = self.values.entry(*asset_identifier).or_insert(0);
-        *current_value -= value
+        *current_value = current_value
+            .checked_sub(value)
+            .ok_or(IronfishError::IllegalValue)?;
+
+        Ok(())
     }
 
     pub fn iter(&self) -> hash_map::Iter<AssetIdentifier, i64> {
@@ -37,11 +61,18 @@ impl ValueBalances {
     }
 }
 
+/// Convert a note, mint, or burn value to a signed balance. Values above
+/// `i64::MAX` would wrap around to negative balances, so they are rejected
+/// with [`IronfishError::IllegalValue`].
+pub(crate) fn signed_value(value: u64) -> Result<i64, IronfishError> {
+    i64::try_from(value).map_err(|_| IronfishError::IllegalValue)
+}
+
 #[cfg(test)]
 mod test {
-    use crate::assets::asset::NATIVE_ASSET;
+    use crate::{assets::asset::NATIVE_ASSET, errors::IronfishError};
 
-    use super::ValueBalances;
+    use super::{signed_value, ValueBalances};
 
     #[test]
     fn test_value_balances_has_native_asset() {
@@ -57,8 +88,8 @@ mod test {
     fn test_value_balances_fee() {
         let mut vb = ValueBalances::new();
 
-        vb.add(&NATIVE_ASSET, 5);
-        vb.subtract(&NATIVE_ASSET, 2);
+        vb.add(&NATIVE_ASSET, 5).unwrap();
+        vb.subtract(&NATIVE_ASSET, 2).unwrap();
 
         assert_eq!(*vb.fee(), 3);
     }
@@ -70,16 +101,51 @@ mod test {
         let asset_two = [1u8; 32];
         let asset_three = [2u8; 32];
 
-        vb.add(&NATIVE_ASSET, 5);
-        vb.subtract(&NATIVE_ASSET, 3);
+        vb.add(&NATIVE_ASSET, 5).unwrap();
+        vb.subtract(&NATIVE_ASSET, 3).unwrap();
 
-        vb.add(&asset_two, 6);
-        vb.subtract(&asset_two, 2);
+        vb.add(&asset_two, 6).unwrap();
+        vb.subtract(&asset_two, 2).unwrap();
 
-        vb.subtract(&asset_three, 10);
+        vb.subtract(&asset_three, 10).unwrap();
 
         assert_eq!(*vb.fee(), 2);
         assert_eq!(*vb.values.get(&asset_two).unwrap(), 4);
         assert_eq!(*vb.values.get(&asset_three).unwrap(), -10);
     }
+
+    #[test]
+    fn test_value_balances_overflow() {
+        let mut vb = ValueBalances::new();
+
+        vb.add(&NATIVE_ASSET, i64::MAX).unwrap();
+        assert!(matches!(
+            vb.add(&NATIVE_ASSET, 1),
+            Err(IronfishError::IllegalValue)
+        ));
+        assert_eq!(*vb.fee(), i64::MAX);
+
+        vb.subtract(&NATIVE_ASSET, i64::MAX).unwrap();
+        vb.subtract(&NATIVE_ASSET, i64::MAX).unwrap();
+        vb.subtract(&NATIVE_ASSET, 1).unwrap();
+        assert!(matches!(
+            vb.subtract(&NATIVE_ASSET, 1),
+            Err(IronfishError::IllegalValue)
+        ));
+        assert_eq!(*vb.fee(), i64::MIN);
+    }
+
+    #[test]
+    fn test_signed_value() {
+        assert_eq!(signed_value(0).unwrap(), 0);
+        assert_eq!(signed_value(i64::MAX as u64).unwrap(), i64::MAX);
+        assert!(matches!(
+            signed_value(i64::MAX as u64 + 1),
+            Err(IronfishError::IllegalValue)
+        ));
+        assert!(matches!(
+            signed_value(u64::MAX),
+            Err(IronfishError::IllegalValue)
+        ));
+    }
 }
@@ -1,13 +1,111 @@
 use bellman::{groth16, Circuit};
+use blake2b_simd::Params as Blake2b;
 use bls12_381::Bls12;
-use ironfish_zkp::proofs::{Output, Spend};
-use rand::thread_rng;
+use ironfish_zkp::{
+    circuits::{asset_output::AssetOutput, asset_spend::AssetSpend, mint_asset::MintAsset},
+    mpc::{self, MpcParameters},
+    proofs::{Output, Spend},
+};
+use rand::{thread_rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
-use std::{env, fs::File};
+use std::{
+    env,
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
 
 const TREE_DEPTH: usize = 32;
 
-const ALLOWED_ARGUMENTS: [&str; 3] = ["all", "spend", "output"];
+const ALLOWED_ARGUMENTS: [&str; 6] = [
+    "all",
+    "spend",
+    "output",
+    "mint",
+    "asset-spend",
+    "asset-output",
+];
+
+const USAGE: &str = "Usage:
+  generate_params <circuit>                             Generate params for a circuit with a single party
+  generate_params init <circuit> <phase1> <out>         Start a setup ceremony for a circuit
+  generate_params contribute <in> <out>                 Add a contribution with fresh entropy
+  generate_params verify <before> <after>               Verify the contribution that turned <before> into <after>
+  generate_params finalize <circuit> <phase1> <in> <out>
+                                                        Verify every contribution and write the result as a .params file
+
+<phase1> is a directory holding the phase1radix2m* files of a powers of tau ceremony.";
+
+fn spend_circuit() -> Spend {
+    Spend {
+        value_commitment: None,
+        proof_generation_key: None,
+        payment_address: None,
+        commitment_randomness: None,
+        ar: None,
+        auth_path: vec![None; TREE_DEPTH],
+        anchor: None,
+    }
+}
+
+fn output_circuit() -> Output {
+    Output {
+        value_commitment: None,
+        payment_address: None,
+        commitment_randomness: None,
+        esk: None,
+    }
+}
+
+fn mint_circuit() -> MintAsset {
+    MintAsset {
+        name: [0u8; 32],
+        chain: [0u8; 32],
+        network: [0u8; 32],
+        token_identifier: [0u8; 32],
+        nonce: 0,
+        asset_authorization_key: None,
+    }
+}
+
+fn asset_spend_circuit() -> AssetSpend {
+    AssetSpend {
+        value_commitment: None,
+        proof_generation_key: None,
+        payment_address: None,
+        commitment_randomness: None,
+        ar: None,
+        auth_path: vec![None; TREE_DEPTH],
+        anchor: None,
+    }
+}
+
+fn asset_output_circuit() -> AssetOutput {
+    AssetOutput {
+        value_commitment: None,
+        payment_address: None,
+        commitment_randomness: None,
+        esk: None,
+    }
+}
+
+fn write_params(full_filename: &str, groth_params: &groth16::Parameters<Bls12>) {
+    let mut bytes = vec![];
+    groth_params.write(&mut bytes).unwrap();
+
+    let mut buffer = File::create(full_filename).unwrap();
+    buffer.write_all(&bytes).unwrap();
+
+    // Pin these in ironfish-rust so the file is verified when it's loaded
+    let hash = Blake2b::new().hash_length(64).hash(&bytes);
+    println!("BLAKE2b digest: {}", hash.to_hex());
+
+    let mut vk_bytes = vec![];
+    groth_params.vk.write(&mut vk_bytes).unwrap();
+    let vk_hash = Blake2b::new().hash_length(64).hash(&vk_bytes);
+    println!("Verifying key BLAKE2b digest: {}", vk_hash.to_hex());
+}
 
 fn generate_params(filename: &str, circuit: impl Circuit<bls12_381::Scalar>) {
     let full_filename = format!("{filename}.params");
@@ -17,14 +115,155 @@ fn generate_params(filename: &str, circuit: impl Circuit<bls12_381::Scalar>) {
     println!("Creating params at {full_filename}");
     let groth_params = groth16::generate_random_parameters::<Bls12, _, _>(circuit, rng).unwrap();
 
-    let mut buffer = File::create(full_filename).unwrap();
-    groth_params.write(&mut buffer).unwrap();
+    write_params(&full_filename, &groth_params);
+}
+
+fn read_mpc_params(filename: &str) -> MpcParameters {
+    let file = File::open(filename).unwrap();
+
+    // Files come from other participants, so check every point
+    MpcParameters::read(BufReader::new(file), true).unwrap()
+}
+
+fn write_mpc_params(filename: &str, params: &MpcParameters) {
+    let file = File::create(filename).unwrap();
+    let mut writer = BufWriter::new(file);
+    params.write(&mut writer).unwrap();
+    writer.flush().unwrap();
+}
+
+/// Compute the initial ceremony parameters for a circuit from the powers of
+/// tau transcripts in `phase1`. Nothing about them is secret, so everyone gets
+/// the same parameters.
+fn initial_mpc_params(circuit: &str, phase1: &str) -> Option<MpcParameters> {
+    let open_phase1 = |exponent: u32| {
+        let path = Path::new(phase1).join(format!("phase1radix2m{exponent}"));
+        println!("Reading {}", path.display());
+        File::open(path).map(|file| BufReader::with_capacity(1024 * 1024, file))
+    };
+
+    let params = match circuit {
+        "spend" => MpcParameters::new(spend_circuit(), open_phase1),
+        "output" => MpcParameters::new(output_circuit(), open_phase1),
+        "mint" => MpcParameters::new(mint_circuit(), open_phase1),
+        "asset-spend" => MpcParameters::new(asset_spend_circuit(), open_phase1),
+        "asset-output" => MpcParameters::new(asset_output_circuit(), open_phase1),
+        _ => {
+            println!(
+                "Invalid choice {:?}. Valid choices: {:?}",
+                circuit,
+                &ALLOWED_ARGUMENTS[1..]
+            );
+            return None;
+        }
+    };
+
+    Some(params.unwrap())
+}
+
+fn init(circuit: &str, phase1: &str, out: &str) {
+    println!("Initializing {circuit} ceremony at {out}");
+    if let Some(params) = initial_mpc_params(circuit, phase1) {
+        write_mpc_params(out, &params);
+    }
+}
+
+fn contribute(input: &str, out: &str) {
+    let mut params = read_mpc_params(input);
+
+    // Mix the system randomness with some typed by the participant, so a
+    // compromised system rng alone isn't enough to recover the contribution
+    println!("Type some random text and press [ENTER] to provide additional entropy...");
+    let mut user_entropy = String::new();
+    io::stdin().read_line(&mut user_entropy).unwrap();
+
+    let mut system_entropy = [0u8; 64];
+    thread_rng().fill_bytes(&mut system_entropy);
+
+    let seed = Blake2b::new()
+        .hash_length(32)
+        .to_state()
+        .update(&system_entropy)
+        .update(user_entropy.as_bytes())
+        .finalize();
+    let mut seed_bytes = [0u8; 32];
+    seed_bytes.copy_from_slice(seed.as_bytes());
+
+    let hash = params.contribute(&mut ChaCha20Rng::from_seed(seed_bytes));
+    write_mpc_params(out, &params);
+
+    println!(
+        "Contribution #{} written to {out}",
+        params.contributions().len()
+    );
+    println!("Publish your contribution hash: {}", hex(&hash));
+}
+
+fn verify(before: &str, after: &str) {
+    let before = read_mpc_params(before);
+    let after = read_mpc_params(after);
+
+    match mpc::verify_contribution(&before, &after) {
+        Ok(hash) => {
+            println!(
+                "Contribution #{} is valid: {}",
+                after.contributions().len(),
+                hex(&hash)
+            );
+        }
+        Err(e) => {
+            println!("Contribution is invalid: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn finalize(circuit: &str, phase1: &str, input: &str, out: &str) {
+    let params = read_mpc_params(input);
+
+    // Nobody in the chain has to be trusted, so check every contribution
+    // against parameters computed from scratch
+    let initial = match initial_mpc_params(circuit, phase1) {
+        Some(initial) => initial,
+        None => return,
+    };
+    let hashes = match mpc::verify_contributions(&initial, &params) {
+        Ok(hashes) => hashes,
+        Err(e) => {
+            println!("Ceremony is invalid: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Contributions:");
+    for (i, hash) in hashes.iter().enumerate() {
+        println!("  #{}: {}", i + 1, hex(hash));
+    }
+
+    println!("Creating params at {out}");
+    write_params(out, params.params());
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
+    if args.len() > 2 {
+        match (&args[1][..], args.len()) {
+            ("init", 5) => init(&args[2].to_lowercase(), &args[3], &args[4]),
+            ("contribute", 4) => contribute(&args[2], &args[3]),
+            ("verify", 4) => verify(&args[2], &args[3]),
+            ("finalize", 6) => finalize(&args[2].to_lowercase(), &args[3], &args[4], &args[5]),
+            _ => println!("{USAGE}"),
+        }
+        return;
+    }
+
     if args.len() != 2 {
+        println!("{USAGE}");
         println!(
             "You must provide a circuit choice. Valid choices: {:?}",
             ALLOWED_ARGUMENTS
@@ -43,29 +282,22 @@ fn main() {
     }
 
     if circuit == "all" || circuit == "spend" {
-        generate_params(
-            "sapling-spend",
-            Spend {
-                value_commitment: None,
-                proof_generation_key: None,
-                payment_address: None,
-                commitment_randomness: None,
-                ar: None,
-                auth_path: vec![None; TREE_DEPTH],
-                anchor: None,
-            },
-        );
+        generate_params("sapling-spend", spend_circuit());
     }
 
     if circuit == "all" || circuit == "output" {
-        generate_params(
-            "sapling-output",
-            Output {
-                value_commitment: None,
-                payment_address: None,
-                commitment_randomness: None,
-                esk: None,
-            },
-        );
+        generate_params("sapling-output", output_circuit());
+    }
+
+    if circuit == "all" || circuit == "mint" {
+        generate_params("sapling-mint", mint_circuit());
+    }
+
+    if circuit == "all" || circuit == "asset-spend" {
+        generate_params("sapling-asset-spend", asset_spend_circuit());
+    }
+
+    if circuit == "all" || circuit == "asset-output" {
+        generate_params("sapling-asset-output", asset_output_circuit());
     }
 }
@@ -49,6 +49,10 @@ impl Circuit<bls12_381::Scalar> for MintAsset {
         asset_public_key
             .assert_not_small_order(cs.namespace(|| "asset_public_key not small order"))?;
 
+        // Expose the asset public key so signatures made with the asset
+        // authorization key can be bound to this proof
+        asset_public_key.inputize(cs.namespace(|| "asset public key"))?;
+
         // Create the Asset Info pre-image
         let identifier_preimage = asset_info_preimage(
             &mut cs.namespace(|| "asset info preimage"),
@@ -85,7 +89,7 @@ mod test {
         Circuit,
     };
     use ff::Field;
-    use group::GroupEncoding;
+    use group::{Curve, GroupEncoding};
     use rand::{rngs::StdRng, SeedableRng};
 
     use crate::constants::{
@@ -126,8 +130,14 @@ mod test {
             .update(&asset_plaintext)
             .finalize();
 
+        let asset_public_key_point = jubjub::ExtendedPoint::from(asset_public_key).to_affine();
+        let mut public_inputs = vec![
+            asset_public_key_point.get_u(),
+            asset_public_key_point.get_v(),
+        ];
+
         let identifier_bits = multipack::bytes_to_bits_le(identifier.as_bytes());
-        let public_inputs = multipack::compute_multipacking(&identifier_bits);
+        public_inputs.extend(multipack::compute_multipacking(&identifier_bits));
 
         // Mint proof
         let circuit = MintAsset {
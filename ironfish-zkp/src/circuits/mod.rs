@@ -1,3 +1,5 @@
+pub mod asset_output;
+pub mod asset_spend;
 pub mod mint_asset;
 pub mod output;
 pub mod spend;
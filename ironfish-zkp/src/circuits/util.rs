@@ -1,11 +1,19 @@
 use std::slice;
 
 use bellman::{
-    gadgets::boolean::{self, AllocatedBit, Boolean},
+    gadgets::{
+        blake2s,
+        boolean::{self, AllocatedBit, Boolean},
+    },
     ConstraintSystem, SynthesisError,
 };
 use ff::PrimeField;
-use zcash_primitives::sapling::ValueCommitment;
+use zcash_primitives::{
+    constants::{GH_FIRST_BLOCK, VALUE_COMMITMENT_GENERATOR_PERSONALIZATION},
+    sapling::ValueCommitment,
+};
+
+use crate::primitives::AssetValueCommitment;
 use zcash_proofs::{
     circuit::ecc::{self, EdwardsPoint},
     constants::{VALUE_COMMITMENT_RANDOMNESS_GENERATOR, VALUE_COMMITMENT_VALUE_GENERATOR},
@@ -132,3 +140,107 @@ where
 
     Ok(value_bits)
 }
+
+/// Derives an asset's value generator from its identifier the same way
+/// `group_hash` does natively. The point the identifier hashes to is
+/// witnessed, constrained to match the hash, and has its cofactor cleared, so
+/// the prover can't pick a generator whose discrete log they know.
+pub fn asset_value_generator<CS>(
+    mut cs: CS,
+    asset_identifier: Option<[u8; 32]>,
+    asset_generator: Option<jubjub::ExtendedPoint>,
+) -> Result<EdwardsPoint, SynthesisError>
+where
+    CS: ConstraintSystem<bls12_381::Scalar>,
+{
+    // The first block of the hash is constant, followed by the identifier
+    let mut preimage: Vec<Boolean> = GH_FIRST_BLOCK
+        .iter()
+        .flat_map(|&b| (0..8).map(move |i| Boolean::constant((b >> i) & 1 == 1)))
+        .collect();
+    preimage.extend(slice_into_boolean_vec_le(
+        cs.namespace(|| "booleanize asset identifier"),
+        asset_identifier.as_ref().map(|i| &i[..]),
+        32,
+    )?);
+
+    let hash = blake2s::blake2s(
+        cs.namespace(|| "asset identifier hash"),
+        &preimage,
+        VALUE_COMMITMENT_GENERATOR_PERSONALIZATION,
+    )?;
+
+    let asset_generator =
+        EdwardsPoint::witness(cs.namespace(|| "witness asset generator"), asset_generator)?;
+
+    // The hash must be the encoding of the witnessed point. The representation
+    // is strict, so a hash that isn't a canonical encoding can't be satisfied.
+    let asset_generator_repr =
+        asset_generator.repr(cs.namespace(|| "representation of asset generator"))?;
+    for (i, (a, b)) in asset_generator_repr.iter().zip(hash.iter()).enumerate() {
+        Boolean::enforce_equal(
+            cs.namespace(|| format!("asset generator bit {} matches hash", i)),
+            a,
+            b,
+        )?;
+    }
+
+    // Clear the cofactor by doubling three times
+    let value_generator = asset_generator
+        .double(cs.namespace(|| "asset generator doubling 1"))?
+        .double(cs.namespace(|| "asset generator doubling 2"))?
+        .double(cs.namespace(|| "asset generator doubling 3"))?;
+
+    // The identity would let the prover commit to any value
+    value_generator
+        .assert_not_small_order(cs.namespace(|| "asset value generator not small order"))?;
+
+    Ok(value_generator)
+}
+
+/// Exposes a Pedersen commitment to the value as an input to the circuit,
+/// using the given asset value generator instead of the native one
+pub fn expose_asset_value_commitment<CS>(
+    mut cs: CS,
+    value_generator: &EdwardsPoint,
+    value_commitment: Option<AssetValueCommitment>,
+) -> Result<Vec<boolean::Boolean>, SynthesisError>
+where
+    CS: ConstraintSystem<bls12_381::Scalar>,
+{
+    // Booleanize the value into little-endian bit order
+    let value_bits = boolean::u64_into_boolean_vec_le(
+        cs.namespace(|| "value"),
+        value_commitment.as_ref().map(|c| c.value),
+    )?;
+
+    // Compute the note value in the exponent. The generator is not fixed, so
+    // this is a variable base multiplication.
+    let value = value_generator.mul(
+        cs.namespace(|| "compute the value in the exponent"),
+        &value_bits,
+    )?;
+
+    // Booleanize the randomness. This does not ensure
+    // the bit representation is "in the field" because
+    // it doesn't matter for security.
+    let rcv = boolean::field_into_boolean_vec_le(
+        cs.namespace(|| "rcv"),
+        value_commitment.as_ref().map(|c| c.randomness),
+    )?;
+
+    // Compute the randomness in the exponent
+    let rcv = ecc::fixed_base_multiplication(
+        cs.namespace(|| "computation of rcv"),
+        &VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
+        &rcv,
+    )?;
+
+    // Compute the Pedersen commitment to the value
+    let cv = value.add(cs.namespace(|| "computation of cv"), &rcv)?;
+
+    // Expose the commitment as an input to the circuit
+    cv.inputize(cs.namespace(|| "commitment point"))?;
+
+    Ok(value_bits)
+}
@@ -1,5 +1,7 @@
 pub mod circuits;
 pub mod constants;
+pub mod mpc;
+pub mod primitives;
 
 pub use zcash_primitives::sapling::{
     group_hash::group_hash, pedersen_hash, redjubjub, Diversifier, Note as SaplingNote, Nullifier,
@@ -0,0 +1,877 @@
+//! Phase 2 of a multi-party computation ceremony for the Groth16 parameters.
+//!
+//! The ceremony starts from parameters computed by [`MpcParameters::new`] from
+//! a circuit and the transcript of a powers of tau ceremony (phase 1), such as
+//! the one run for Zcash. `gamma` is fixed to 1 and `delta` starts out as 1, so
+//! the initial parameters hold no secret of their own and anyone can recompute
+//! them. Each participant then rerandomizes `delta` with a secret of their own
+//! and publishes a [`PublicKey`] proving they did so honestly. As long as one
+//! participant destroys their secret, nobody knows the final `delta` and can't
+//! forge proofs.
+//!
+//! Single steps can be checked with [`verify_contribution`], and the whole
+//! ceremony with [`verify_contributions`] starting from recomputed initial
+//! parameters.
+
+use std::{
+    io::{self, Read, Write},
+    sync::Arc,
+};
+
+use bellman::{
+    groth16::{Parameters, VerifyingKey},
+    Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
+};
+use blake2b_simd::Params as Blake2b;
+use bls12_381::{pairing, Bls12, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use ff::{Field, PrimeField};
+use group::{Curve, Group};
+use rand::{thread_rng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Groth16 parameters along with the history of contributions made to them.
+#[derive(Clone)]
+pub struct MpcParameters {
+    params: Parameters<Bls12>,
+
+    /// BLAKE2b digest of the parameters as they were initialized. Ties every
+    /// contribution to the circuit the ceremony is for.
+    cs_hash: [u8; 64],
+
+    contributions: Vec<PublicKey>,
+}
+
+impl MpcParameters {
+    /// Compute the initial parameters for `circuit`, before any contributions
+    /// have been made, from a powers of tau transcript.
+    ///
+    /// `open_phase1` is called with the base 2 logarithm of the number of
+    /// constraints the transcript has to cover, and has to open the matching
+    /// transcript. Transcripts are in the `phase1radix2m<exponent>` layout of
+    /// the Zcash powers of tau tooling: `alpha` in G1, `beta` in G1 and G2, the
+    /// Lagrange coefficients of `tau` in G1 and G2, of `alpha * tau` and
+    /// `beta * tau` in G1, and finally the `h` query, all uncompressed.
+    pub fn new<C, F, R>(circuit: C, open_phase1: F) -> Result<Self, SynthesisError>
+    where
+        C: Circuit<Scalar>,
+        F: FnOnce(u32) -> io::Result<R>,
+        R: Read,
+    {
+        let mut assembly = KeypairAssembly::default();
+        assembly.alloc_input(|| "", || Ok(Scalar::one()))?;
+        circuit.synthesize(&mut assembly)?;
+
+        // Constrain every input, so the IC query is fully dense
+        for i in 0..assembly.num_inputs {
+            assembly.enforce(
+                || "",
+                |lc| lc + Variable::new_unchecked(Index::Input(i)),
+                |lc| lc,
+                |lc| lc,
+            );
+        }
+
+        let mut m = 1;
+        let mut exponent = 0;
+        while m < assembly.num_constraints {
+            m *= 2;
+            exponent += 1;
+
+            if exponent > Scalar::S {
+                return Err(SynthesisError::PolynomialDegreeTooLarge);
+            }
+        }
+
+        let mut reader = open_phase1(exponent)?;
+        let alpha_g1 = read_g1(&mut reader)?;
+        let beta_g1 = read_g1(&mut reader)?;
+        let beta_g2 = read_g2(&mut reader)?;
+        let phase1 = Phase1 {
+            coeffs_g1: (0..m)
+                .map(|_| read_g1(&mut reader))
+                .collect::<io::Result<_>>()?,
+            coeffs_g2: (0..m)
+                .map(|_| read_g2(&mut reader))
+                .collect::<io::Result<_>>()?,
+            alpha_coeffs_g1: (0..m)
+                .map(|_| read_g1(&mut reader))
+                .collect::<io::Result<_>>()?,
+            beta_coeffs_g1: (0..m)
+                .map(|_| read_g1(&mut reader))
+                .collect::<io::Result<_>>()?,
+        };
+        let h: Vec<G1Affine> = (0..m - 1)
+            .map(|_| read_g1(&mut reader))
+            .collect::<io::Result<_>>()?;
+
+        let (a_inputs, b_g1_inputs, b_g2_inputs, ic) = phase1.evaluate(
+            &assembly.at_inputs,
+            &assembly.bt_inputs,
+            &assembly.ct_inputs,
+        );
+        let (a_aux, b_g1_aux, b_g2_aux, l) =
+            phase1.evaluate(&assembly.at_aux, &assembly.bt_aux, &assembly.ct_aux);
+
+        // Every auxiliary variable has to be constrained, so the L query is
+        // fully dense
+        if l.iter().any(|p| bool::from(p.is_identity())) {
+            return Err(SynthesisError::UnconstrainedVariable);
+        }
+
+        let params = Parameters {
+            vk: VerifyingKey {
+                alpha_g1,
+                beta_g1,
+                beta_g2,
+                gamma_g2: G2Affine::generator(),
+                delta_g1: G1Affine::generator(),
+                delta_g2: G2Affine::generator(),
+                ic: ic.iter().map(|p| p.to_affine()).collect(),
+            },
+            h: Arc::new(h),
+            l: Arc::new(l.iter().map(|p| p.to_affine()).collect()),
+            // Points at infinity are left out of the A and B queries
+            a: Arc::new(non_identity(a_inputs.into_iter().chain(a_aux))),
+            b_g1: Arc::new(non_identity(b_g1_inputs.into_iter().chain(b_g1_aux))),
+            b_g2: Arc::new(non_identity(b_g2_inputs.into_iter().chain(b_g2_aux))),
+        };
+
+        let mut hasher = Blake2b::new().hash_length(64).to_state();
+        params.write(&mut hasher)?;
+
+        let mut cs_hash = [0u8; 64];
+        cs_hash.copy_from_slice(hasher.finalize().as_bytes());
+
+        Ok(MpcParameters {
+            params,
+            cs_hash,
+            contributions: vec![],
+        })
+    }
+
+    /// The parameters as of the latest contribution.
+    pub fn params(&self) -> &Parameters<Bls12> {
+        &self.params
+    }
+
+    pub fn contributions(&self) -> &[PublicKey] {
+        &self.contributions
+    }
+
+    /// Rerandomize the parameters with a secret sampled from `rng`. The secret
+    /// is dropped before returning.
+    ///
+    /// Returns the hash of the new contribution, which the participant should
+    /// publish so others can check it was included in the final parameters.
+    pub fn contribute<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> [u8; 64] {
+        let delta = loop {
+            let delta = Scalar::random(&mut *rng);
+            if !bool::from(delta.is_zero()) {
+                break delta;
+            }
+        };
+        let delta_inverse = delta.invert().unwrap();
+
+        let delta_after = (self.params.vk.delta_g1 * delta).to_affine();
+        let public_key =
+            PublicKey::new(&self.cs_hash, &self.contributions, delta_after, delta, rng);
+
+        self.params.vk.delta_g1 = delta_after;
+        self.params.vk.delta_g2 = (self.params.vk.delta_g2 * delta).to_affine();
+        self.params.h = Arc::new(batch_mul(&self.params.h, delta_inverse));
+        self.params.l = Arc::new(batch_mul(&self.params.l, delta_inverse));
+
+        let hash = public_key.hash();
+        self.contributions.push(public_key);
+
+        hash
+    }
+
+    /// Load [`MpcParameters`] from a Read implementation. `checked` verifies
+    /// that every point is in the right subgroup, which should be done for
+    /// files received from other participants.
+    pub fn read<R: Read>(mut reader: R, checked: bool) -> io::Result<Self> {
+        let params = Parameters::read(&mut reader, checked)?;
+
+        let mut cs_hash = [0u8; 64];
+        reader.read_exact(&mut cs_hash)?;
+
+        let mut num_contributions = [0u8; 4];
+        reader.read_exact(&mut num_contributions)?;
+
+        let mut contributions = vec![];
+        for _ in 0..u32::from_be_bytes(num_contributions) {
+            contributions.push(PublicKey::read(&mut reader)?);
+        }
+
+        Ok(MpcParameters {
+            params,
+            cs_hash,
+            contributions,
+        })
+    }
+
+    /// Stow the bytes of these [`MpcParameters`] in the given writer.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.params.write(&mut writer)?;
+        writer.write_all(&self.cs_hash)?;
+        writer.write_all(&(self.contributions.len() as u32).to_be_bytes())?;
+        for contribution in &self.contributions {
+            contribution.write(&mut writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Published by a participant to prove they know the secret `delta` they
+/// multiplied into the parameters.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    /// `delta_g1` of the parameters after this contribution
+    delta_after: G1Affine,
+
+    /// Random point and the same point multiplied by the secret
+    s: G1Affine,
+    s_delta: G1Affine,
+
+    /// Point derived from the transcript, multiplied by the secret
+    r_delta: G2Affine,
+
+    /// Hash of the circuit, all previous contributions, `s` and `s_delta`
+    transcript: [u8; 64],
+}
+
+impl PublicKey {
+    fn new<R: RngCore + CryptoRng>(
+        cs_hash: &[u8; 64],
+        contributions: &[PublicKey],
+        delta_after: G1Affine,
+        delta: Scalar,
+        rng: &mut R,
+    ) -> Self {
+        let s = G1Projective::random(&mut *rng).to_affine();
+        let s_delta = (s * delta).to_affine();
+
+        let transcript = transcript_hash(cs_hash, contributions, &s, &s_delta);
+        let r_delta = (hash_to_g2(&transcript) * delta).to_affine();
+
+        PublicKey {
+            delta_after,
+            s,
+            s_delta,
+            r_delta,
+            transcript,
+        }
+    }
+
+    /// BLAKE2b digest identifying this contribution.
+    pub fn hash(&self) -> [u8; 64] {
+        let mut hasher = Blake2b::new().hash_length(64).to_state();
+        self.write(&mut hasher).unwrap();
+
+        let mut hash = [0u8; 64];
+        hash.copy_from_slice(hasher.finalize().as_bytes());
+
+        hash
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let delta_after = read_g1(&mut reader)?;
+        let s = read_g1(&mut reader)?;
+        let s_delta = read_g1(&mut reader)?;
+
+        let r_delta = read_g2(&mut reader)?;
+
+        let mut transcript = [0u8; 64];
+        reader.read_exact(&mut transcript)?;
+
+        Ok(PublicKey {
+            delta_after,
+            s,
+            s_delta,
+            r_delta,
+            transcript,
+        })
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.delta_after.to_uncompressed())?;
+        writer.write_all(&self.s.to_uncompressed())?;
+        writer.write_all(&self.s_delta.to_uncompressed())?;
+        writer.write_all(&self.r_delta.to_uncompressed())?;
+        writer.write_all(&self.transcript)?;
+
+        Ok(())
+    }
+}
+
+/// Reasons a contribution can fail verification.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ContributionError {
+    /// The parameters were initialized for different circuits
+    CircuitMismatch,
+    /// The new parameters don't extend the previous ones by exactly one
+    /// contribution
+    ContributionMismatch,
+    /// Parameters other than `delta` were changed
+    ParametersModified,
+    /// The public key doesn't prove knowledge of the secret, or doesn't match
+    /// the change made to the parameters
+    InvalidPublicKey,
+}
+
+/// Check that `after` is `before` with exactly one valid contribution applied.
+///
+/// Returns the hash of that contribution.
+pub fn verify_contribution(
+    before: &MpcParameters,
+    after: &MpcParameters,
+) -> Result<[u8; 64], ContributionError> {
+    if after.contributions.len() != before.contributions.len() + 1 {
+        return Err(ContributionError::ContributionMismatch);
+    }
+
+    let hashes = verify_contributions(before, after)?;
+
+    Ok(hashes[0])
+}
+
+/// Check that `after` is `before` with any number of valid contributions
+/// applied in turn. Passing the initial parameters recomputed with
+/// [`MpcParameters::new`] as `before` checks the whole ceremony.
+///
+/// Returns the hashes of the contributions made since `before`.
+pub fn verify_contributions(
+    before: &MpcParameters,
+    after: &MpcParameters,
+) -> Result<Vec<[u8; 64]>, ContributionError> {
+    if before.cs_hash != after.cs_hash {
+        return Err(ContributionError::CircuitMismatch);
+    }
+
+    if after.contributions.len() < before.contributions.len()
+        || after.contributions[..before.contributions.len()] != before.contributions[..]
+    {
+        return Err(ContributionError::ContributionMismatch);
+    }
+
+    let (old, new) = (&before.params, &after.params);
+    if old.vk.alpha_g1 != new.vk.alpha_g1
+        || old.vk.beta_g1 != new.vk.beta_g1
+        || old.vk.beta_g2 != new.vk.beta_g2
+        || old.vk.gamma_g2 != new.vk.gamma_g2
+        || old.vk.ic != new.vk.ic
+        || old.a != new.a
+        || old.b_g1 != new.b_g1
+        || old.b_g2 != new.b_g2
+        || old.h.len() != new.h.len()
+        || old.l.len() != new.l.len()
+    {
+        return Err(ContributionError::ParametersModified);
+    }
+
+    let mut delta_g1 = old.vk.delta_g1;
+    let mut hashes = vec![];
+    for (index, public_key) in after
+        .contributions
+        .iter()
+        .enumerate()
+        .skip(before.contributions.len())
+    {
+        let transcript = transcript_hash(
+            &after.cs_hash,
+            &after.contributions[..index],
+            &public_key.s,
+            &public_key.s_delta,
+        );
+        if public_key.transcript != transcript {
+            return Err(ContributionError::InvalidPublicKey);
+        }
+
+        if bool::from(public_key.s.is_identity())
+            || bool::from(public_key.delta_after.is_identity())
+        {
+            return Err(ContributionError::InvalidPublicKey);
+        }
+
+        let r = hash_to_g2(&transcript);
+
+        // The participant knows the secret they multiplied `s` by...
+        if !same_ratio((public_key.s, public_key.s_delta), (r, public_key.r_delta)) {
+            return Err(ContributionError::InvalidPublicKey);
+        }
+
+        // ...and it is the same secret `delta` was multiplied by
+        if !same_ratio((delta_g1, public_key.delta_after), (r, public_key.r_delta)) {
+            return Err(ContributionError::InvalidPublicKey);
+        }
+
+        delta_g1 = public_key.delta_after;
+        hashes.push(public_key.hash());
+    }
+
+    if delta_g1 != new.vk.delta_g1 {
+        return Err(ContributionError::InvalidPublicKey);
+    }
+
+    if !same_ratio(
+        (G1Affine::generator(), new.vk.delta_g1),
+        (G2Affine::generator(), new.vk.delta_g2),
+    ) {
+        return Err(ContributionError::InvalidPublicKey);
+    }
+
+    // `h` and `l` were divided by that secret
+    if !same_ratio(
+        merge_pairs(&old.h, &new.h),
+        (new.vk.delta_g2, old.vk.delta_g2),
+    ) || !same_ratio(
+        merge_pairs(&old.l, &new.l),
+        (new.vk.delta_g2, old.vk.delta_g2),
+    ) {
+        return Err(ContributionError::ParametersModified);
+    }
+
+    Ok(hashes)
+}
+
+fn transcript_hash(
+    cs_hash: &[u8; 64],
+    contributions: &[PublicKey],
+    s: &G1Affine,
+    s_delta: &G1Affine,
+) -> [u8; 64] {
+    let mut hasher = Blake2b::new().hash_length(64).to_state();
+    hasher.update(cs_hash);
+    for contribution in contributions {
+        contribution.write(&mut hasher).unwrap();
+    }
+    hasher.update(&s.to_uncompressed());
+    hasher.update(&s_delta.to_uncompressed());
+
+    let mut hash = [0u8; 64];
+    hash.copy_from_slice(hasher.finalize().as_bytes());
+
+    hash
+}
+
+/// Deterministically map a transcript hash to a G2 point whose discrete log
+/// nobody knows.
+fn hash_to_g2(digest: &[u8; 64]) -> G2Affine {
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&digest[..32]);
+
+    G2Projective::random(ChaCha20Rng::from_seed(seed)).to_affine()
+}
+
+/// Check that `g1.0 / g1.1 == g2.0 / g2.1` in the exponent.
+fn same_ratio(g1: (G1Affine, G1Affine), g2: (G2Affine, G2Affine)) -> bool {
+    pairing(&g1.0, &g2.1) == pairing(&g1.1, &g2.0)
+}
+
+/// Compress two lists of points into a single pair using a random linear
+/// combination, so they can be checked with [`same_ratio`] at once.
+fn merge_pairs(v1: &[G1Affine], v2: &[G1Affine]) -> (G1Affine, G1Affine) {
+    let mut rng = thread_rng();
+
+    let mut s = G1Projective::identity();
+    let mut s_delta = G1Projective::identity();
+    for (a, b) in v1.iter().zip(v2.iter()) {
+        let rho = Scalar::random(&mut rng);
+        s += a * rho;
+        s_delta += b * rho;
+    }
+
+    (s.to_affine(), s_delta.to_affine())
+}
+
+/// Lagrange coefficients from a powers of tau transcript, used to evaluate the
+/// QAP polynomials of a circuit at `tau`.
+struct Phase1 {
+    coeffs_g1: Vec<G1Affine>,
+    coeffs_g2: Vec<G2Affine>,
+    alpha_coeffs_g1: Vec<G1Affine>,
+    beta_coeffs_g1: Vec<G1Affine>,
+}
+
+impl Phase1 {
+    /// Evaluate the QAP polynomials of each variable, given as the
+    /// coefficients they have in each constraint. Returns the A query, both B
+    /// queries, and `beta * A + alpha * B + C` for every variable.
+    #[allow(clippy::type_complexity)]
+    fn evaluate(
+        &self,
+        at: &[Vec<(Scalar, usize)>],
+        bt: &[Vec<(Scalar, usize)>],
+        ct: &[Vec<(Scalar, usize)>],
+    ) -> (
+        Vec<G1Projective>,
+        Vec<G1Projective>,
+        Vec<G2Projective>,
+        Vec<G1Projective>,
+    ) {
+        let mut a_g1 = vec![G1Projective::identity(); at.len()];
+        let mut b_g1 = vec![G1Projective::identity(); at.len()];
+        let mut b_g2 = vec![G2Projective::identity(); at.len()];
+        let mut ext = vec![G1Projective::identity(); at.len()];
+
+        for (i, ((at, bt), ct)) in at.iter().zip(bt).zip(ct).enumerate() {
+            for &(coeff, constraint) in at {
+                a_g1[i] += self.coeffs_g1[constraint] * coeff;
+                ext[i] += self.beta_coeffs_g1[constraint] * coeff;
+            }
+
+            for &(coeff, constraint) in bt {
+                b_g1[i] += self.coeffs_g1[constraint] * coeff;
+                b_g2[i] += self.coeffs_g2[constraint] * coeff;
+                ext[i] += self.alpha_coeffs_g1[constraint] * coeff;
+            }
+
+            for &(coeff, constraint) in ct {
+                ext[i] += self.coeffs_g1[constraint] * coeff;
+            }
+        }
+
+        (a_g1, b_g1, b_g2, ext)
+    }
+}
+
+/// Records the coefficients every variable of a circuit has in each
+/// constraint, which make up its QAP polynomials.
+#[derive(Default)]
+struct KeypairAssembly {
+    num_inputs: usize,
+    num_aux: usize,
+    num_constraints: usize,
+    at_inputs: Vec<Vec<(Scalar, usize)>>,
+    bt_inputs: Vec<Vec<(Scalar, usize)>>,
+    ct_inputs: Vec<Vec<(Scalar, usize)>>,
+    at_aux: Vec<Vec<(Scalar, usize)>>,
+    bt_aux: Vec<Vec<(Scalar, usize)>>,
+    ct_aux: Vec<Vec<(Scalar, usize)>>,
+}
+
+impl ConstraintSystem<Scalar> for KeypairAssembly {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // There are no assignments, so the function providing one isn't called
+        let index = self.num_aux;
+        self.num_aux += 1;
+
+        self.at_aux.push(vec![]);
+        self.bt_aux.push(vec![]);
+        self.ct_aux.push(vec![]);
+
+        Ok(Variable::new_unchecked(Index::Aux(index)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _: A, _: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.num_inputs;
+        self.num_inputs += 1;
+
+        self.at_inputs.push(vec![]);
+        self.bt_inputs.push(vec![]);
+        self.ct_inputs.push(vec![]);
+
+        Ok(Variable::new_unchecked(Index::Input(index)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        fn record(
+            lc: LinearCombination<Scalar>,
+            inputs: &mut [Vec<(Scalar, usize)>],
+            aux: &mut [Vec<(Scalar, usize)>],
+            constraint: usize,
+        ) {
+            for &(variable, coeff) in lc.as_ref() {
+                match variable.get_unchecked() {
+                    Index::Input(i) => inputs[i].push((coeff, constraint)),
+                    Index::Aux(i) => aux[i].push((coeff, constraint)),
+                }
+            }
+        }
+
+        let constraint = self.num_constraints;
+        record(
+            a(LinearCombination::zero()),
+            &mut self.at_inputs,
+            &mut self.at_aux,
+            constraint,
+        );
+        record(
+            b(LinearCombination::zero()),
+            &mut self.bt_inputs,
+            &mut self.bt_aux,
+            constraint,
+        );
+        record(
+            c(LinearCombination::zero()),
+            &mut self.ct_inputs,
+            &mut self.ct_aux,
+            constraint,
+        );
+
+        self.num_constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+fn non_identity<P: Curve>(points: impl Iterator<Item = P>) -> Vec<P::AffineRepr> {
+    points
+        .filter(|p| !bool::from(p.is_identity()))
+        .map(|p| p.to_affine())
+        .collect()
+}
+
+fn batch_mul(points: &[G1Affine], scalar: Scalar) -> Vec<G1Affine> {
+    let projective: Vec<G1Projective> = points.iter().map(|p| p * scalar).collect();
+
+    let mut affine = vec![G1Affine::identity(); projective.len()];
+    G1Projective::batch_normalize(&projective, &mut affine);
+
+    affine
+}
+
+fn read_g1<R: Read>(mut reader: R) -> io::Result<G1Affine> {
+    let mut bytes = [0u8; 96];
+    reader.read_exact(&mut bytes)?;
+
+    Option::from(G1Affine::from_uncompressed(&bytes))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid G1 point"))
+}
+
+fn read_g2<R: Read>(mut reader: R) -> io::Result<G2Affine> {
+    let mut bytes = [0u8; 192];
+    reader.read_exact(&mut bytes)?;
+
+    Option::from(G2Affine::from_uncompressed(&bytes))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid G2 point"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use bellman::{groth16, Circuit, ConstraintSystem, SynthesisError};
+    use bls12_381::{G1Affine, G2Affine, Scalar};
+    use ff::{Field, PrimeField};
+    use group::Curve;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{verify_contribution, verify_contributions, ContributionError, MpcParameters};
+
+    /// Proves knowledge of a square root of the public input
+    struct Square {
+        x: Option<Scalar>,
+    }
+
+    impl Circuit<Scalar> for Square {
+        fn synthesize<CS: ConstraintSystem<Scalar>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            let x = cs.alloc(|| "x", || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let x_squared = cs.alloc_input(
+                || "x squared",
+                || {
+                    self.x
+                        .map(|x| x.square())
+                        .ok_or(SynthesisError::AssignmentMissing)
+                },
+            )?;
+            cs.enforce(
+                || "x * x = x squared",
+                |lc| lc + x,
+                |lc| lc + x,
+                |lc| lc + x_squared,
+            );
+
+            Ok(())
+        }
+    }
+
+    /// Powers of tau transcript for a domain of `2^exponent` constraints, made
+    /// from secrets drawn from `seed`.
+    fn phase1_transcript(exponent: u32, seed: u64) -> io::Cursor<Vec<u8>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let tau = Scalar::random(&mut rng);
+        let alpha = Scalar::random(&mut rng);
+        let beta = Scalar::random(&mut rng);
+
+        let m = 1u64 << exponent;
+        let omega = (exponent..Scalar::S).fold(Scalar::root_of_unity(), |w, _| w.square());
+        let t = tau.pow_vartime(&[m]) - Scalar::one();
+        let m_inverse = Scalar::from(m).invert().unwrap();
+
+        // Lagrange basis polynomials of the domain, evaluated at tau
+        let mut lagrange = vec![];
+        let mut omega_i = Scalar::one();
+        for _ in 0..m {
+            lagrange.push(omega_i * t * m_inverse * (tau - omega_i).invert().unwrap());
+            omega_i *= omega;
+        }
+
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&(g1 * alpha).to_affine().to_uncompressed());
+        bytes.extend_from_slice(&(g1 * beta).to_affine().to_uncompressed());
+        bytes.extend_from_slice(&(g2 * beta).to_affine().to_uncompressed());
+        for l in &lagrange {
+            bytes.extend_from_slice(&(g1 * l).to_affine().to_uncompressed());
+        }
+        for l in &lagrange {
+            bytes.extend_from_slice(&(g2 * l).to_affine().to_uncompressed());
+        }
+        for l in &lagrange {
+            bytes.extend_from_slice(&(g1 * (alpha * l)).to_affine().to_uncompressed());
+        }
+        for l in &lagrange {
+            bytes.extend_from_slice(&(g1 * (beta * l)).to_affine().to_uncompressed());
+        }
+        let mut tau_i = Scalar::one();
+        for _ in 0..m - 1 {
+            bytes.extend_from_slice(&(g1 * (tau_i * t)).to_affine().to_uncompressed());
+            tau_i *= tau;
+        }
+
+        io::Cursor::new(bytes)
+    }
+
+    fn initial_params(seed: u64) -> MpcParameters {
+        MpcParameters::new(Square { x: None }, |exponent| {
+            // One constraint for the square, and one for each input
+            assert_eq!(exponent, 2);
+            Ok(phase1_transcript(exponent, seed))
+        })
+        .expect("can initialize parameters")
+    }
+
+    #[test]
+    fn test_ceremony() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let initial = initial_params(0);
+
+        // Anyone can recompute the initial parameters
+        let mut bytes = vec![];
+        initial.write(&mut bytes).unwrap();
+        let mut recomputed = vec![];
+        initial_params(0).write(&mut recomputed).unwrap();
+        assert_eq!(bytes, recomputed);
+
+        let mut first = initial.clone();
+        let first_hash = first.contribute(&mut rng);
+        assert_eq!(verify_contribution(&initial, &first), Ok(first_hash));
+
+        let mut second = first.clone();
+        let second_hash = second.contribute(&mut rng);
+        assert_eq!(verify_contribution(&first, &second), Ok(second_hash));
+
+        // Skipping a step is not a single contribution
+        assert_eq!(
+            verify_contribution(&initial, &second).err(),
+            Some(ContributionError::ContributionMismatch)
+        );
+
+        // Round trip through serialization
+        let mut bytes = vec![];
+        second.write(&mut bytes).unwrap();
+        let deserialized = MpcParameters::read(&bytes[..], true).expect("can read parameters");
+        assert_eq!(verify_contribution(&first, &deserialized), Ok(second_hash));
+
+        // The whole ceremony checks out from the initial parameters
+        assert_eq!(
+            verify_contributions(&initial, &second),
+            Ok(vec![first_hash, second_hash])
+        );
+
+        // The initial parameters can be used for proving too, and so can the
+        // final ones
+        let x = Scalar::from(3);
+        let proof = groth16::create_random_proof(Square { x: Some(x) }, initial.params(), &mut rng)
+            .expect("can create proof");
+        let pvk = groth16::prepare_verifying_key(&initial.params().vk);
+        assert!(groth16::verify_proof(&pvk, &proof, &[x.square()]).is_ok());
+
+        let proof = groth16::create_random_proof(Square { x: Some(x) }, second.params(), &mut rng)
+            .expect("can create proof");
+        let pvk = groth16::prepare_verifying_key(&second.params().vk);
+        assert!(groth16::verify_proof(&pvk, &proof, &[x.square()]).is_ok());
+        assert!(groth16::verify_proof(&pvk, &proof, &[x]).is_err());
+    }
+
+    #[test]
+    fn test_tampered_contribution() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let initial = initial_params(0);
+        let mut after = initial.clone();
+        after.contribute(&mut rng);
+
+        // Changing delta without a matching public key
+        let mut tampered = after.clone();
+        tampered.params.vk.delta_g1 = initial.params.vk.delta_g1;
+        assert_eq!(
+            verify_contribution(&initial, &tampered).err(),
+            Some(ContributionError::InvalidPublicKey)
+        );
+
+        // Changing h without dividing by delta
+        let mut tampered = after.clone();
+        tampered.params.h = initial.params.h.clone();
+        assert_eq!(
+            verify_contribution(&initial, &tampered).err(),
+            Some(ContributionError::ParametersModified)
+        );
+
+        // Contributions to parameters from a different transcript
+        let other = initial_params(1);
+        assert_eq!(
+            verify_contribution(&other, &after).err(),
+            Some(ContributionError::CircuitMismatch)
+        );
+
+        // An earlier contribution replaced after the fact
+        let mut later = after.clone();
+        later.contribute(&mut rng);
+        let mut forged = initial.clone();
+        forged.contribute(&mut rng);
+        later.contributions[0] = forged.contributions[0].clone();
+        assert_eq!(
+            verify_contributions(&initial, &later).err(),
+            Some(ContributionError::InvalidPublicKey)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use group::GroupEncoding;
+use jubjub::{ExtendedPoint, SubgroupPoint};
+use zcash_primitives::{
+    constants::{
+        GH_FIRST_BLOCK, NOTE_COMMITMENT_RANDOMNESS_GENERATOR,
+        VALUE_COMMITMENT_GENERATOR_PERSONALIZATION, VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
+    },
+    sapling::pedersen_hash::{pedersen_hash, Personalization},
+};
+
+/// Hash an asset identifier to a point on the Jubjub curve without clearing
+/// the cofactor. Multiplying the result by the cofactor gives the same point as
+/// `group_hash(asset_identifier, VALUE_COMMITMENT_GENERATOR_PERSONALIZATION)`,
+/// and `None` is returned in exactly the cases `group_hash` would return it.
+///
+/// The asset aware circuits witness this point and clear the cofactor
+/// themselves, which guarantees the value generator they commit with is in the
+/// prime order subgroup.
+pub fn asset_generator_base(asset_identifier: &[u8; 32]) -> Option<ExtendedPoint> {
+    let h = blake2s_simd::Params::new()
+        .hash_length(32)
+        .personal(VALUE_COMMITMENT_GENERATOR_PERSONALIZATION)
+        .to_state()
+        .update(GH_FIRST_BLOCK)
+        .update(asset_identifier)
+        .finalize();
+
+    let p = Option::<ExtendedPoint>::from(ExtendedPoint::from_bytes(h.as_array()))?;
+
+    if p.is_small_order().into() {
+        None
+    } else {
+        Some(p)
+    }
+}
+
+/// Pedersen commitment to a value of a specific asset. The same as
+/// [`ValueCommitment`](crate::ValueCommitment), except the value is committed
+/// to with a generator derived from the asset instead of the native value
+/// commitment generator.
+#[derive(Clone)]
+pub struct AssetValueCommitment {
+    pub value: u64,
+    pub randomness: jubjub::Fr,
+
+    /// Identifier of the asset the value is denominated in
+    pub asset_identifier: [u8; 32],
+
+    /// Point the asset's value generator is derived from. Must be
+    /// [`asset_generator_base`] of the identifier, or the circuits will not
+    /// be satisfied.
+    pub asset_generator: ExtendedPoint,
+}
+
+impl AssetValueCommitment {
+    /// Returns `None` if the asset identifier doesn't hash to a valid
+    /// generator
+    pub fn new(value: u64, randomness: jubjub::Fr, asset_identifier: [u8; 32]) -> Option<Self> {
+        let asset_generator = asset_generator_base(&asset_identifier)?;
+
+        Some(AssetValueCommitment {
+            value,
+            randomness,
+            asset_identifier,
+            asset_generator,
+        })
+    }
+
+    /// The generator the value is committed to with
+    pub fn value_generator(&self) -> ExtendedPoint {
+        self.asset_generator.mul_by_cofactor()
+    }
+
+    pub fn commitment(&self) -> ExtendedPoint {
+        (self.value_generator() * jubjub::Fr::from(self.value))
+            + (VALUE_COMMITMENT_RANDOMNESS_GENERATOR * self.randomness)
+    }
+}
+
+/// Compute the full note commitment point for a note of a specific asset. The
+/// same as the Sapling note commitment, with the value generator of the asset
+/// appended to the note contents so the note is bound to its asset.
+pub fn asset_note_commitment(
+    value: u64,
+    g_d: &SubgroupPoint,
+    pk_d: &SubgroupPoint,
+    value_generator: &ExtendedPoint,
+    commitment_randomness: jubjub::Fr,
+) -> ExtendedPoint {
+    let mut note_contents = vec![];
+    note_contents.extend_from_slice(&value.to_le_bytes());
+    note_contents.extend_from_slice(&g_d.to_bytes());
+    note_contents.extend_from_slice(&pk_d.to_bytes());
+    note_contents.extend_from_slice(&value_generator.to_bytes());
+
+    let hash_of_contents = pedersen_hash(
+        Personalization::NoteCommitment,
+        note_contents
+            .into_iter()
+            .flat_map(|byte| (0..8).map(move |i| ((byte >> i) & 1) == 1)),
+    );
+
+    ExtendedPoint::from(hash_of_contents)
+        + (NOTE_COMMITMENT_RANDOMNESS_GENERATOR * commitment_randomness)
+}
+
+#[cfg(test)]
+mod test {
+    use group::cofactor::CofactorGroup;
+    use rand::{RngCore, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    use zcash_primitives::constants::VALUE_COMMITMENT_GENERATOR_PERSONALIZATION;
+
+    use crate::group_hash;
+
+    use super::asset_generator_base;
+
+    #[test]
+    fn test_asset_generator_base_matches_group_hash() {
+        let mut rng = XorShiftRng::from_seed([
+            0x58, 0x62, 0xbe, 0x3d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+
+        for _ in 0..100 {
+            let mut asset_identifier = [0u8; 32];
+            rng.fill_bytes(&mut asset_identifier);
+
+            let expected = group_hash(
+                &asset_identifier,
+                VALUE_COMMITMENT_GENERATOR_PERSONALIZATION,
+            );
+            let base = asset_generator_base(&asset_identifier);
+
+            assert_eq!(expected.is_some(), base.is_some());
+            if let (Some(expected), Some(base)) = (expected, base) {
+                assert_eq!(base.clear_cofactor(), expected);
+            }
+        }
+    }
+}
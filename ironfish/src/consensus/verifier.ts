@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+import { isExpiredSequence } from '@ironfish/rust-nodejs'
 import { BufferSet } from 'buffer-map'
 import { Blockchain } from '../blockchain'
 import {
@@ -292,7 +293,7 @@ export class Verifier {
   }
 
   isExpiredSequence(expirationSequence: number, sequence: number): boolean {
-    return expirationSequence !== 0 && expirationSequence <= sequence
+    return isExpiredSequence(expirationSequence, sequence)
   }
 
   /**
@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+import { ENCRYPTED_NOTE_LENGTH } from '@ironfish/rust-nodejs'
+import bufio from 'bufio'
+import { Transaction } from './transaction'
+
+const TRANSACTION_VERSION_FLAG = 2 ** 31
+
+function serializeTransaction(version: number): Buffer {
+  const bw = bufio.write()
+
+  if (version >= 2) {
+    bw.writeU32(TRANSACTION_VERSION_FLAG + version)
+  }
+
+  bw.writeU64(1) // spends
+  bw.writeU64(1) // notes
+  if (version >= 2) {
+    bw.writeU64(0) // mints
+    bw.writeU64(0) // burns
+  }
+  bw.writeI64(5) // fee
+  bw.writeU32(10) // expiration sequence
+
+  // spend
+  bw.writeBytes(Buffer.alloc(192 + 32 + 32))
+  bw.writeHash(Buffer.alloc(32, 1))
+  bw.writeU32(3)
+  bw.writeHash(Buffer.alloc(32, 2))
+  bw.writeBytes(Buffer.alloc(64))
+
+  // note
+  bw.writeBytes(Buffer.alloc(192))
+  bw.writeBytes(Buffer.alloc(ENCRYPTED_NOTE_LENGTH, 3))
+
+  bw.writeBytes(Buffer.alloc(64, 4))
+
+  return bw.render()
+}
+
+describe('Transaction', () => {
+  it.each([1, 2])('parses a V%i transaction', (version) => {
+    const transaction = new Transaction(serializeTransaction(version))
+
+    expect(transaction.version()).toEqual(version)
+    expect(transaction.fee()).toEqual(BigInt(5))
+    expect(transaction.expirationSequence()).toEqual(10)
+
+    expect(transaction.spendsLength()).toEqual(1)
+    const spend = transaction.getSpend(0)
+    expect(spend.commitment).toEqual(Buffer.alloc(32, 1))
+    expect(spend.size).toEqual(3)
+    expect(spend.nullifier).toEqual(Buffer.alloc(32, 2))
+
+    expect(transaction.notesLength()).toEqual(1)
+    expect(transaction.transactionSignature()).toEqual(Buffer.alloc(64, 4))
+  })
+})
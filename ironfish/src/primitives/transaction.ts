@@ -12,9 +12,12 @@ export type TransactionHash = Buffer
 
 export type SerializedTransaction = Buffer
 
+const TRANSACTION_VERSION_FLAG = 2 ** 31
+
 export class Transaction {
   private readonly transactionPostedSerialized: Buffer
 
+  private readonly _version: number
   private readonly _fee: bigint
   private readonly _expirationSequence: number
   private readonly _spends: Spend[] = []
@@ -31,8 +34,28 @@ export class Transaction {
 
     const reader = bufio.read(this.transactionPostedSerialized, true)
 
-    const _spendsLength = reader.readU64() // 8
+    // Versioned transactions start with a header with the top bit set. V1
+    // transactions have no header and start with the spend count instead.
+    let _spendsLength
+    const header = reader.readU32() // 4
+    if (header >= TRANSACTION_VERSION_FLAG) {
+      this._version = header - TRANSACTION_VERSION_FLAG
+      _spendsLength = reader.readU64() // 8
+    } else {
+      this._version = 1
+      _spendsLength = header + reader.readU32() * 2 ** 32 // 8
+    }
+
     const _notesLength = reader.readU64() // 8
+
+    // V1 transactions have no mints or burns
+    let _mintsLength = 0
+    let _burnsLength = 0
+    if (this._version >= 2) {
+      _mintsLength = reader.readU64() // 8
+      _burnsLength = reader.readU64() // 8
+    }
+
     this._fee = BigInt(reader.readI64()) // 8
     this._expirationSequence = reader.readU32() // 4
 
@@ -66,6 +89,26 @@ export class Transaction {
       return new NoteEncrypted(reader.readBytes(ENCRYPTED_NOTE_LENGTH, true))
     })
 
+    for (let i = 0; i < _mintsLength; i++) {
+      // proof
+      reader.seek(192)
+      // asset public key
+      reader.seek(32)
+      // asset identifier
+      reader.seek(32)
+      // value
+      reader.seek(8)
+      // signature
+      reader.seek(64)
+    }
+
+    for (let i = 0; i < _burnsLength; i++) {
+      // asset identifier
+      reader.seek(32)
+      // value
+      reader.seek(8)
+    }
+
     this._signature = reader.readBytes(64, true)
   }
 
@@ -110,6 +153,13 @@ export class Transaction {
     return result
   }
 
+  /**
+   * The version of the transaction, which determines its serialized format.
+   */
+  version(): number {
+    return this._version
+  }
+
   /**
    * The number of notes in the transaction.
    */
@@ -182,7 +232,7 @@ export class Transaction {
    * is signed when the transaction is created
    */
   unsignedHash(): TransactionHash {
-    this._unsignedHash = this._unsignedHash || this.withReference((t) => t.hash())
+    this._unsignedHash = this._unsignedHash || this.withReference((t) => t.signatureHash())
     return this._unsignedHash
   }
 
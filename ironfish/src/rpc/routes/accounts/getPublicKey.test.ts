@@ -17,6 +17,7 @@ jest.mock('@ironfish/rust-nodejs', () => {
     generateNewPublicAddress: jest.fn().mockReturnValue({
       public_address:
         'f0486664761c625bad7024f11724b42968244a4bb05446b17719bcc48d5fb65899da4fb204358a3b9d6d05',
+      diversifier_index: BigInt(1),
     }),
   }
 })
@@ -57,6 +58,6 @@ describe('Route account/getPublicKey', () => {
 
     expect(response.status).toBe(200)
     expect(response.content.account).toEqual(account.name)
-    expect(response.content.publicAddress).not.toEqual(publicAddress)
+    expect(response.content.publicKey).not.toEqual(publicAddress)
   })
 })
@@ -97,7 +97,7 @@ describe('Demonstrate the Sapling API', () => {
     it('Can verify the miner transaction', () => {
       const serializedTransaction = minerTransaction.serialize()
       const deserializedTransaction = new NativeTransactionPosted(serializedTransaction)
-      expect(deserializedTransaction.verify()).toBeTruthy()
+      expect(deserializedTransaction.verify().valid).toBe(true)
     })
 
     it('Can add the miner transaction note to the tree', async () => {
@@ -132,7 +132,7 @@ describe('Demonstrate the Sapling API', () => {
     })
 
     it('Can verify the transaction', async () => {
-      expect(publicTransaction.verify()).toBeTruthy()
+      expect(publicTransaction.verify().valid).toBe(true)
       for (let i = 0; i < publicTransaction.notesLength(); i++) {
         const note = Buffer.from(publicTransaction.getNote(i))
         await tree.add(new NoteEncrypted(note))
@@ -145,7 +145,7 @@ describe('Demonstrate the Sapling API', () => {
     })
 
     it('Exposes transaction hash', () => {
-      expect(publicTransaction.hash().length).toBe(32)
+      expect(publicTransaction.signatureHash().length).toBe(32)
     })
   })
 
@@ -268,7 +268,7 @@ describe('Demonstrate the Sapling API', () => {
         transaction.post(undefined, BigInt(1)),
       )
       expect(postedTransaction).toBeTruthy()
-      expect(postedTransaction.verify()).toBeTruthy()
+      expect(postedTransaction.verify().valid).toBe(true)
     })
   })
 })
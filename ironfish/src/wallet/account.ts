@@ -28,6 +28,7 @@ export class Account {
   readonly incomingViewKey: string
   readonly outgoingViewKey: string
   publicAddress: string
+  diversifierIndex: number
   readonly prefix: Buffer
   readonly prefixRange: DatabaseKeyRange
 
@@ -38,6 +39,7 @@ export class Account {
     incomingViewKey,
     outgoingViewKey,
     publicAddress,
+    diversifierIndex,
     walletDb,
   }: {
     id: string
@@ -46,6 +48,7 @@ export class Account {
     incomingViewKey: string
     outgoingViewKey: string
     publicAddress: string
+    diversifierIndex?: number
     walletDb: WalletDB
   }) {
     this.id = id
@@ -54,6 +57,7 @@ export class Account {
     this.incomingViewKey = incomingViewKey
     this.outgoingViewKey = outgoingViewKey
     this.publicAddress = publicAddress
+    this.diversifierIndex = diversifierIndex ?? 0
 
     this.prefix = calculateAccountPrefix(id)
     this.prefixRange = StorageUtils.getPrefixKeyRange(this.prefix)
@@ -71,6 +75,7 @@ export class Account {
       incomingViewKey: this.incomingViewKey,
       outgoingViewKey: this.outgoingViewKey,
       publicAddress: this.publicAddress,
+      diversifierIndex: this.diversifierIndex,
     }
   }
 
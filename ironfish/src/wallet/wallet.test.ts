@@ -511,6 +511,30 @@ describe('Accounts', () => {
     })
   })
 
+  describe('generateNewPublicAddress', () => {
+    it('should generate a different address every time', async () => {
+      const { node } = nodeTest
+
+      const account = await node.wallet.createAccount('accountA')
+      const defaultAddress = account.publicAddress
+
+      await node.wallet.generateNewPublicAddress(account)
+      const firstAddress = account.publicAddress
+      const firstIndex = account.diversifierIndex
+      expect(firstAddress).not.toEqual(defaultAddress)
+
+      await node.wallet.generateNewPublicAddress(account)
+      expect(account.publicAddress).not.toEqual(firstAddress)
+      expect(account.publicAddress).not.toEqual(defaultAddress)
+      expect(account.diversifierIndex).toBeGreaterThan(firstIndex)
+
+      // The index is stored, so the wallet continues where it left off
+      const stored = await node.wallet.walletDb.accounts.get(account.id)
+      expect(stored?.publicAddress).toEqual(account.publicAddress)
+      expect(stored?.diversifierIndex).toEqual(account.diversifierIndex)
+    })
+  })
+
   describe('expireTransactions', () => {
     it('should not expire transactions with expiration sequence ahead of the chain', async () => {
       const { node } = nodeTest
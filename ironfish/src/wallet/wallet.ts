@@ -1144,8 +1144,20 @@ export class Wallet {
 
   async generateNewPublicAddress(account: Account): Promise<void> {
     this.assertHasAccount(account)
-    const key = generateNewPublicAddress(account.spendingKey)
-    account.publicAddress = key.public_address
+
+    let address = generateNewPublicAddress(
+      account.spendingKey,
+      BigInt(account.diversifierIndex) + 1n,
+    )
+
+    // Accounts that never generated an address don't know the index of their
+    // default address, so the search can land on it
+    if (address.public_address === account.publicAddress) {
+      address = generateNewPublicAddress(account.spendingKey, address.diversifier_index + 1n)
+    }
+
+    account.publicAddress = address.public_address
+    account.diversifierIndex = Number(address.diversifier_index)
     await this.walletDb.setAccount(account)
   }
 
@@ -16,9 +16,27 @@ describe('AccountValueEncoding', () => {
       outgoingViewKey: key.outgoing_view_key,
       publicAddress: key.public_address,
       spendingKey: key.spending_key,
+      diversifierIndex: 5,
     }
     const buffer = encoder.serialize(value)
     const deserializedValue = encoder.deserialize(buffer)
     expect(deserializedValue).toEqual(value)
   })
+
+  it('deserializes accounts stored without a diversifier index', () => {
+    const encoder = new AccountValueEncoding()
+
+    const key = generateKey()
+    const value: AccountValue = {
+      id: 'id',
+      name: 'foobar',
+      incomingViewKey: key.incoming_view_key,
+      outgoingViewKey: key.outgoing_view_key,
+      publicAddress: key.public_address,
+      spendingKey: key.spending_key,
+    }
+    const buffer = encoder.serialize(value)
+    const deserializedValue = encoder.deserialize(buffer.subarray(0, buffer.length - 8))
+    expect(deserializedValue).toEqual({ ...value, diversifierIndex: 0 })
+  })
 })
@@ -14,6 +14,11 @@ export interface AccountValue {
   incomingViewKey: string
   outgoingViewKey: string
   publicAddress: string
+  /**
+   * Diversifier index of the public address. Accounts that never generated a
+   * new address, and accounts stored before the index was, leave this out.
+   */
+  diversifierIndex?: number
 }
 
 export class AccountValueEncoding implements IDatabaseEncoding<AccountValue> {
@@ -25,6 +30,7 @@ export class AccountValueEncoding implements IDatabaseEncoding<AccountValue> {
     bw.writeBytes(Buffer.from(value.incomingViewKey, 'hex'))
     bw.writeBytes(Buffer.from(value.outgoingViewKey, 'hex'))
     bw.writeBytes(Buffer.from(value.publicAddress, 'hex'))
+    bw.writeU64(value.diversifierIndex ?? 0)
 
     return bw.render()
   }
@@ -37,6 +43,8 @@ export class AccountValueEncoding implements IDatabaseEncoding<AccountValue> {
     const incomingViewKey = reader.readBytes(KEY_LENGTH).toString('hex')
     const outgoingViewKey = reader.readBytes(KEY_LENGTH).toString('hex')
     const publicAddress = reader.readBytes(PUBLIC_ADDRESS_LENGTH).toString('hex')
+    // Accounts stored before the diversifier index end here
+    const diversifierIndex = reader.left() > 0 ? reader.readU64() : 0
 
     return {
       id,
@@ -45,6 +53,7 @@ export class AccountValueEncoding implements IDatabaseEncoding<AccountValue> {
       incomingViewKey,
       outgoingViewKey,
       publicAddress,
+      diversifierIndex,
     }
   }
 
@@ -56,6 +65,7 @@ export class AccountValueEncoding implements IDatabaseEncoding<AccountValue> {
     size += KEY_LENGTH
     size += KEY_LENGTH
     size += PUBLIC_ADDRESS_LENGTH
+    size += 8
 
     return size
   }
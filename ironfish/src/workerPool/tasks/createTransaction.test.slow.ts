@@ -144,7 +144,7 @@ describe('CreateTransactionTask', () => {
       const transactionPosted = new TransactionPosted(
         Buffer.from(response.serializedTransactionPosted),
       )
-      expect(transactionPosted.verify()).toBe(true)
+      expect(transactionPosted.verify().valid).toBe(true)
       expect(transactionPosted.notesLength()).toBe(2)
       const decryptedNote = new NoteEncrypted(transactionPosted.getNote(0)).decryptNoteForOwner(
         account.incomingViewKey,
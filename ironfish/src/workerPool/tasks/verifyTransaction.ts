@@ -89,7 +89,7 @@ export class VerifyTransactionTask extends WorkerTask {
         throw new Error('Transaction has negative fees')
       }
 
-      verified = transaction.verify()
+      verified = transaction.verify().valid
     } catch {
       verified = false
     }
@@ -86,7 +86,7 @@ export class VerifyTransactionsTask extends WorkerTask {
     let verified = false
 
     try {
-      verified = verifyTransactions(transactionsPosted)
+      verified = verifyTransactions(transactionsPosted).valid
     } catch (e) {
       verified = false
     }